@@ -0,0 +1,23 @@
+-- Contadores de intentos fallidos de login (por cuenta y por IP)
+CREATE TABLE login_attempts (
+    key VARCHAR(300) PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    window_started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    blocked_until TIMESTAMP WITH TIME ZONE
+);
+
+-- Historial de bloqueos, consultable por administradores
+CREATE TABLE login_lockouts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    scope VARCHAR(20) NOT NULL CHECK (scope IN ('account', 'ip')),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    username VARCHAR(255),
+    ip_address VARCHAR(45),
+    failures INTEGER NOT NULL,
+    locked_until TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE INDEX idx_login_lockouts_created_at ON login_lockouts(created_at DESC);
+CREATE INDEX idx_login_lockouts_user_id ON login_lockouts(user_id);
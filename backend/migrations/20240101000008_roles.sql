@@ -0,0 +1,27 @@
+-- Roles de usuario (RBAC). Todos los usuarios tienen el rol básico "user";
+-- aquí sólo se guardan los roles adicionales. Los permisos de cada rol se definen en el código.
+CREATE TABLE user_roles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('moderator', 'admin')),
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    granted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, role)
+);
+
+-- Historial de cambios de roles
+CREATE TABLE role_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL,
+    action VARCHAR(10) NOT NULL CHECK (action IN ('grant', 'revoke')),
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE INDEX idx_user_roles_role ON user_roles(role);
+CREATE INDEX idx_role_changes_user_id ON role_changes(user_id, created_at DESC);
+
+-- La cuenta administrativa inicial recibe el rol de administrador
+INSERT INTO user_roles (user_id, role)
+SELECT id, 'admin' FROM users WHERE username = 'admin';
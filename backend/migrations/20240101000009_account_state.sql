@@ -0,0 +1,7 @@
+-- Estado de la cuenta que se comprueba en cada petición autenticada
+ALTER TABLE users
+    ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN password_changed_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN banned_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN banned_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN ban_reason TEXT;
@@ -0,0 +1,16 @@
+-- Enlaces de inicio de sesión sin contraseña (un solo uso, ligados al dispositivo que los pidió)
+CREATE TABLE magic_links (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE, -- NULL si el email no corresponde a ninguna cuenta
+    email VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    nonce_hash VARCHAR(64) NOT NULL, -- nonce entregado sólo al dispositivo solicitante
+    ip_address VARCHAR(45),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    used_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE INDEX idx_magic_links_email_created_at ON magic_links(email, created_at);
+CREATE INDEX idx_magic_links_user_id ON magic_links(user_id);
@@ -0,0 +1,18 @@
+-- Cambios de contraseña, email y nombre de usuario
+ALTER TABLE users ADD COLUMN username_changed_at TIMESTAMP WITH TIME ZONE;
+
+-- Los tokens de cambio de email apuntan a la dirección nueva, aún no confirmada
+ALTER TABLE email_verification_tokens ADD COLUMN is_change BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Nombres anteriores: redirigen al actual durante el periodo de gracia
+CREATE TABLE username_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    old_username VARCHAR(30) NOT NULL,
+    new_username VARCHAR(30) NOT NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE INDEX idx_username_history_old_username ON username_history(old_username, changed_at);
+CREATE INDEX idx_username_history_user_id ON username_history(user_id);
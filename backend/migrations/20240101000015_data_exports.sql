@@ -0,0 +1,18 @@
+-- Exportaciones de datos personales, generadas en segundo plano
+CREATE TABLE data_exports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'ready', 'failed', 'expired')),
+    file_path TEXT,
+    size_bytes BIGINT,
+    token_hash VARCHAR(64) UNIQUE, -- enlace de descarga, sólo cuando está lista
+    error TEXT,
+    requested_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Índices
+CREATE INDEX idx_data_exports_user_id ON data_exports(user_id, requested_at DESC);
+CREATE INDEX idx_data_exports_status ON data_exports(status);
@@ -0,0 +1,9 @@
+-- Campos editables del perfil
+ALTER TABLE users ADD COLUMN website VARCHAR(200);
+ALTER TABLE users ADD COLUMN location VARCHAR(100);
+ALTER TABLE users ADD COLUMN pronouns VARCHAR(40);
+ALTER TABLE users ADD COLUMN birthday DATE;
+
+-- Quién ve el cumpleaños: todos (fecha completa), todos sin el año, o sólo el usuario
+ALTER TABLE users ADD COLUMN birthday_visibility VARCHAR(20) NOT NULL DEFAULT 'private'
+    CHECK (birthday_visibility IN ('public', 'month_day', 'private'));
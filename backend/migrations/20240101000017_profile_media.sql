@@ -0,0 +1,5 @@
+-- Avatar y banner subidos: la URL apunta a la variante principal y la clave identifica
+-- todas sus variantes en el almacenamiento
+ALTER TABLE users ADD COLUMN avatar_key VARCHAR(255);
+ALTER TABLE users ADD COLUMN banner_url TEXT;
+ALTER TABLE users ADD COLUMN banner_key VARCHAR(255);
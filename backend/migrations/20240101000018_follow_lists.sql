@@ -0,0 +1,5 @@
+-- Listas paginadas de seguidores y seguidos, de la relación más reciente a la más antigua
+
+-- Índices
+CREATE INDEX idx_follows_following_created_at ON follows(following_id, created_at DESC);
+CREATE INDEX idx_follows_follower_created_at ON follows(follower_id, created_at DESC);
@@ -0,0 +1,15 @@
+-- Cuentas privadas: sólo sus seguidores aprobados ven sus posts y listas
+ALTER TABLE users ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Solicitudes de seguimiento pendientes de aprobar por la cuenta privada
+CREATE TABLE follow_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    requester_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(requester_id, target_id),
+    CHECK(requester_id != target_id)
+);
+
+-- Índices
+CREATE INDEX idx_follow_requests_target_created_at ON follow_requests(target_id, created_at DESC);
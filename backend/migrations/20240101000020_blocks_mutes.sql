@@ -0,0 +1,24 @@
+-- Bloqueos: invisibilidad mutua entre las dos cuentas
+CREATE TABLE user_blocks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    blocker_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE(blocker_id, blocked_id),
+    CHECK(blocker_id != blocked_id)
+);
+
+-- Silenciados: sólo quien silencia deja de ver a la otra cuenta; sin expires_at es indefinido
+CREATE TABLE user_mutes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    muter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    muted_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE(muter_id, muted_id),
+    CHECK(muter_id != muted_id)
+);
+
+-- Índices
+CREATE INDEX idx_user_blocks_blocked_id ON user_blocks(blocked_id);
+CREATE INDEX idx_user_mutes_muter_created_at ON user_mutes(muter_id, created_at DESC);
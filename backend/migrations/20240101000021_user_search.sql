@@ -0,0 +1,13 @@
+-- Búsqueda de usuarios tolerante a erratas y a tildes ("jose" encuentra a "José")
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+-- unaccent() no es IMMUTABLE y no puede usarse en un índice; con el diccionario explícito sí
+CREATE OR REPLACE FUNCTION search_normalize(value TEXT) RETURNS TEXT AS $$
+    SELECT lower(public.unaccent('public.unaccent'::regdictionary, COALESCE(value, '')))
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE;
+
+-- Índices
+CREATE INDEX idx_users_search_trgm ON users
+    USING GIN (search_normalize(username || ' ' || COALESCE(display_name, '')) gin_trgm_ops);
+CREATE INDEX idx_users_username_prefix ON users(lower(username) text_pattern_ops);
@@ -0,0 +1,29 @@
+-- Sugerencias de a quién seguir, precalculadas por usuario en segundo plano
+CREATE TABLE follow_suggestions (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    suggested_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    mutual_count INTEGER NOT NULL DEFAULT 0,       -- seguidos propios que siguen a la cuenta
+    shared_likes_count INTEGER NOT NULL DEFAULT 0, -- posts a los que ambos dieron like
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, suggested_id)
+);
+
+-- Sugerencias descartadas: no vuelven a aparecer
+CREATE TABLE suggestion_dismissals (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    suggested_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    dismissed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, suggested_id)
+);
+
+-- Usuarios cuyas sugerencias hay que recalcular (cambió su grafo o quedaron antiguas)
+CREATE TABLE suggestion_queue (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    queued_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE INDEX idx_follow_suggestions_user_score ON follow_suggestions(user_id, score DESC);
+CREATE INDEX idx_suggestion_queue_queued_at ON suggestion_queue(queued_at);
+CREATE INDEX idx_likes_user_created_at ON likes(user_id, created_at DESC);
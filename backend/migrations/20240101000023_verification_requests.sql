@@ -0,0 +1,43 @@
+-- Insignia de cuenta verificada; is_verified indica si la cuenta la tiene
+ALTER TABLE users
+    ADD COLUMN verification_badge VARCHAR(20)
+        CHECK (verification_badge IN ('person', 'organization', 'government')),
+    ADD COLUMN verified_at TIMESTAMP WITH TIME ZONE;
+
+-- Las cuentas ya verificadas quedan con la insignia de persona
+UPDATE users SET verification_badge = 'person', verified_at = NOW() WHERE is_verified = true;
+
+-- Solicitudes de verificación que revisa la administración
+CREATE TABLE verification_requests (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    badge_type VARCHAR(20) NOT NULL CHECK (badge_type IN ('person', 'organization', 'government')),
+    legal_name VARCHAR(200) NOT NULL,
+    details TEXT NOT NULL,                       -- por qué la cuenta es de interés público
+    links TEXT[] NOT NULL DEFAULT '{}',          -- enlaces que respaldan la solicitud
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'rejected', 'cancelled')),
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    review_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    reviewed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Historial de verificaciones concedidas y retiradas (changed_by NULL: retirada automática)
+CREATE TABLE verification_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action VARCHAR(10) NOT NULL CHECK (action IN ('grant', 'revoke')),
+    badge_type VARCHAR(20) NOT NULL,
+    request_id UUID REFERENCES verification_requests(id) ON DELETE SET NULL,
+    reason TEXT,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Índices
+CREATE UNIQUE INDEX idx_verification_requests_one_pending
+    ON verification_requests(user_id) WHERE status = 'pending';
+CREATE INDEX idx_verification_requests_status ON verification_requests(status, created_at);
+CREATE INDEX idx_verification_requests_user_id ON verification_requests(user_id, created_at DESC);
+CREATE INDEX idx_verification_changes_user_id ON verification_changes(user_id, created_at DESC);
@@ -0,0 +1,6 @@
+-- Posts fijados en el perfil de su autor (NULL: sin fijar)
+ALTER TABLE posts ADD COLUMN pinned_at TIMESTAMP WITH TIME ZONE;
+
+-- Índices
+CREATE INDEX idx_posts_user_pinned ON posts(user_id, pinned_at DESC) WHERE pinned_at IS NOT NULL;
+CREATE INDEX idx_posts_user_created_at ON posts(user_id, created_at DESC);
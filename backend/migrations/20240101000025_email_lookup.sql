@@ -0,0 +1,3 @@
+-- Un email por cuenta sin distinguir mayúsculas; también sirve para buscar por email
+-- (enlaces mágicos, cambio de email)
+CREATE UNIQUE INDEX idx_users_email_lower ON users(LOWER(email));
@@ -0,0 +1,3 @@
+-- Al vincular un proveedor, el callback debe llegar desde el mismo navegador que inició
+-- la autorización: sólo ese cliente recibe el nonce
+ALTER TABLE oauth_states ADD COLUMN link_nonce_hash VARCHAR(64);
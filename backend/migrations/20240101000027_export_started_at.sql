@@ -0,0 +1,3 @@
+-- Momento en que un worker tomó la exportación; sólo las que llevan demasiado tiempo
+-- en proceso vuelven a la cola
+ALTER TABLE data_exports ADD COLUMN started_at TIMESTAMP WITH TIME ZONE;
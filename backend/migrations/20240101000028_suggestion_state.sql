@@ -0,0 +1,8 @@
+-- Último cálculo de sugerencias por usuario; un cálculo sin resultados también cuenta
+CREATE TABLE suggestion_state (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO suggestion_state (user_id, computed_at)
+SELECT user_id, MIN(computed_at) FROM follow_suggestions GROUP BY user_id;
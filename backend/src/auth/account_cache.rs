@@ -0,0 +1,122 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::models::{AccessTokenState, AccountState};
+use crate::repository::{AccessTokenRepository, SessionRepository, UserRepository};
+
+// Por encima de este tamaño se purgan las entradas caducadas al insertar
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Caché en memoria del estado de cuenta, sesión y token personal que comprueba `AuthUser`.
+///
+/// Las entradas viven `ACCOUNT_STATE_CACHE_SECONDS` (30 por defecto). Los cambios hechos por
+/// esta instancia (logout, baneos, cambios de contraseña o de roles) invalidan la entrada al
+/// momento; los hechos por otras instancias se ven como mucho tras ese TTL.
+pub struct AccountStateCache {
+    user_repo: Arc<UserRepository>,
+    session_repo: Arc<SessionRepository>,
+    access_token_repo: Arc<AccessTokenRepository>,
+    ttl: Duration,
+    entries: Mutex<HashMap<(Uuid, Uuid), (AccountState, Instant)>>,
+    access_tokens: Mutex<HashMap<String, (AccessTokenState, Instant)>>,
+}
+
+impl AccountStateCache {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        session_repo: Arc<SessionRepository>,
+        access_token_repo: Arc<AccessTokenRepository>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            user_repo,
+            session_repo,
+            access_token_repo,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            access_tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_env(
+        user_repo: Arc<UserRepository>,
+        session_repo: Arc<SessionRepository>,
+        access_token_repo: Arc<AccessTokenRepository>,
+    ) -> Self {
+        let seconds = std::env::var("ACCOUNT_STATE_CACHE_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(30);
+
+        Self::new(user_repo, session_repo, access_token_repo, Duration::from_secs(seconds))
+    }
+
+    /// Estado de la cuenta para la sesión dada; `None` si la sesión no existe.
+    pub async fn get(&self, user_id: Uuid, session_id: Uuid) -> Result<Option<AccountState>> {
+        let key = (user_id, session_id);
+
+        if let Some((state, fetched_at)) = self.entries.lock().unwrap().get(&key) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(Some(*state));
+            }
+        }
+
+        let Some(state) = self.user_repo.account_state(user_id, session_id).await? else {
+            return Ok(None);
+        };
+
+        // Sólo al refrescar la caché se actualiza la última actividad de la sesión
+        if state.session_active {
+            self.session_repo.touch(session_id, user_id).await?;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PRUNE_THRESHOLD {
+            let ttl = self.ttl;
+            entries.retain(|_, (_, fetched_at)| fetched_at.elapsed() < ttl);
+        }
+        entries.insert(key, (state, Instant::now()));
+
+        Ok(Some(state))
+    }
+
+    /// Estado de un token personal a partir de su hash; `None` si no existe.
+    pub async fn get_access_token(&self, token_hash: &str, ip_address: Option<&str>) -> Result<Option<AccessTokenState>> {
+        if let Some((state, fetched_at)) = self.access_tokens.lock().unwrap().get(token_hash) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(Some(state.clone()));
+            }
+        }
+
+        let Some(state) = self.access_token_repo.find_state(token_hash).await? else {
+            return Ok(None);
+        };
+
+        if state.token_active {
+            self.access_token_repo.touch(state.token_id, ip_address).await?;
+        }
+
+        let mut access_tokens = self.access_tokens.lock().unwrap();
+        if access_tokens.len() >= PRUNE_THRESHOLD {
+            let ttl = self.ttl;
+            access_tokens.retain(|_, (_, fetched_at)| fetched_at.elapsed() < ttl);
+        }
+        access_tokens.insert(token_hash.to_string(), (state.clone(), Instant::now()));
+
+        Ok(Some(state))
+    }
+
+    /// Descarta todo lo cacheado de un usuario tras cambiar su cuenta, sesiones o tokens.
+    pub fn invalidate_user(&self, user_id: Uuid) {
+        self.entries.lock().unwrap().retain(|(cached_user_id, _), _| *cached_user_id != user_id);
+        self.access_tokens.lock().unwrap().retain(|_, (state, _)| state.user_id != user_id);
+    }
+
+    /// Descarta un token de acceso personal recién revocado.
+    pub fn invalidate_access_token(&self, token_id: Uuid) {
+        self.access_tokens.lock().unwrap().retain(|_, (state, _)| state.token_id != token_id);
+    }
+}
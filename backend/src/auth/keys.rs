@@ -0,0 +1,147 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm, OctetKeyPairParameters,
+        OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey,
+};
+use ring::{
+    rand::SystemRandom,
+    rsa::PublicKeyComponents,
+    signature::{Ed25519KeyPair, KeyPair, RsaKeyPair},
+};
+use std::path::Path;
+
+/// Clave de firma de JWT identificada por `kid`.
+/// Las claves asimétricas publican su parte pública en el JWKS; las HMAC no.
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub encoding_key: EncodingKey,
+    pub decoding_key: DecodingKey,
+    pub jwk: Option<Jwk>,
+}
+
+impl JwtKey {
+    /// Carga una clave privada PEM: RSA (PKCS#1 o PKCS#8) firma con RS256, Ed25519 con EdDSA.
+    pub fn from_pem(kid: &str, contents: &[u8]) -> Result<Self> {
+        let parsed = pem::parse(contents)
+            .map_err(|e| anyhow::anyhow!("Clave {} no es un PEM válido: {}", kid, e))?;
+
+        match parsed.tag() {
+            "RSA PRIVATE KEY" => {
+                let key_pair = RsaKeyPair::from_der(parsed.contents())
+                    .map_err(|e| anyhow::anyhow!("Clave RSA {} inválida: {}", kid, e))?;
+                Self::rsa(kid, &key_pair, EncodingKey::from_rsa_der(parsed.contents()))
+            }
+            "PRIVATE KEY" => {
+                if let Ok(key_pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(parsed.contents()) {
+                    return Ok(Self::ed25519(kid, &key_pair, EncodingKey::from_ed_der(parsed.contents())));
+                }
+
+                let key_pair = RsaKeyPair::from_pkcs8(parsed.contents())
+                    .map_err(|e| anyhow::anyhow!("Clave {} no es RSA ni Ed25519: {}", kid, e))?;
+                let encoding_key = EncodingKey::from_rsa_pem(contents)
+                    .map_err(|e| anyhow::anyhow!("Clave RSA {} inválida: {}", kid, e))?;
+                Self::rsa(kid, &key_pair, encoding_key)
+            }
+            other => Err(anyhow::anyhow!("Clave {}: tipo PEM no soportado ({})", kid, other)),
+        }
+    }
+
+    /// Genera una clave Ed25519 que sólo vive mientras el proceso esté en marcha.
+    pub fn ephemeral(kid: &str) -> Result<Self> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| anyhow::anyhow!("No se pudo generar una clave Ed25519"))?;
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|e| anyhow::anyhow!("Clave Ed25519 generada inválida: {}", e))?;
+
+        Ok(Self::ed25519(kid, &key_pair, EncodingKey::from_ed_der(pkcs8.as_ref())))
+    }
+
+    /// Secreto compartido HS256; nunca se publica en el JWKS.
+    pub fn hmac(kid: &str, secret: &[u8]) -> Self {
+        Self {
+            kid: kid.to_string(),
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            jwk: None,
+        }
+    }
+
+    fn rsa(kid: &str, key_pair: &RsaKeyPair, encoding_key: EncodingKey) -> Result<Self> {
+        let public = PublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+
+        Ok(Self {
+            kid: kid.to_string(),
+            algorithm: Algorithm::RS256,
+            encoding_key,
+            decoding_key: DecodingKey::from_rsa_raw_components(&public.n, &public.e),
+            jwk: Some(Jwk {
+                common: common_parameters(kid, KeyAlgorithm::RS256),
+                algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(&public.n),
+                    e: URL_SAFE_NO_PAD.encode(&public.e),
+                }),
+            }),
+        })
+    }
+
+    fn ed25519(kid: &str, key_pair: &Ed25519KeyPair, encoding_key: EncodingKey) -> Self {
+        let public = key_pair.public_key().as_ref();
+
+        Self {
+            kid: kid.to_string(),
+            algorithm: Algorithm::EdDSA,
+            encoding_key,
+            decoding_key: DecodingKey::from_ed_der(public),
+            jwk: Some(Jwk {
+                common: common_parameters(kid, KeyAlgorithm::EdDSA),
+                algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(public),
+                }),
+            }),
+        }
+    }
+}
+
+/// Carga todas las claves `<kid>.pem` de un directorio.
+/// Las claves retiradas se dejan en el directorio para que sus tokens sigan validando.
+pub fn load_dir(dir: &Path) -> Result<Vec<JwtKey>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("No se pudo leer {}: {}", dir.display(), e))?;
+
+    let mut keys = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("pem") {
+            continue;
+        }
+
+        let kid = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(kid) => kid.to_string(),
+            None => continue,
+        };
+        let contents = std::fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("No se pudo leer {}: {}", path.display(), e))?;
+        keys.push(JwtKey::from_pem(&kid, &contents)?);
+    }
+
+    keys.sort_by(|a, b| a.kid.cmp(&b.kid));
+    Ok(keys)
+}
+
+fn common_parameters(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(algorithm),
+        key_id: Some(kid.to_string()),
+        ..Default::default()
+    }
+}
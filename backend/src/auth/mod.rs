@@ -1,59 +1,206 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+pub mod account_cache;
+pub mod keys;
+pub mod password;
+pub mod rbac;
+pub mod scopes;
+pub mod totp;
+
+use jsonwebtoken::{decode, decode_header, encode, jwk::JwkSet, Header, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{Duration, Utc};
 use anyhow::Result;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub use account_cache::AccountStateCache;
+
+use crate::models::User;
+use keys::JwtKey;
+use rbac::Role;
+
+// kid asignado al secreto HS256 heredado; los tokens sin `kid` se validan con él
+const LEGACY_KID: &str = "legacy-hs256";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user_id
     pub username: String,
+    pub sid: String, // session_id
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    #[serde(default)]
+    pub ver: i32, // token_version del usuario al emitir el token
     pub exp: usize, // timestamp de expiración
     pub iat: usize, // timestamp de emisión
 }
 
+/// Firma y verifica los access tokens con un conjunto de claves identificadas por `kid`.
+///
+/// Configuración por entorno:
+/// - `JWT_KEYS_DIR`: directorio con claves privadas `<kid>.pem` (RSA → RS256, Ed25519 → EdDSA)
+/// - `JWT_ACTIVE_KID`: clave con la que se firman los tokens nuevos (por defecto, el mayor `kid`)
+/// - `JWT_SECRET`: secreto HS256 antiguo, sólo para validar tokens emitidos antes de migrar
+/// - `JWT_ACCESS_TTL_MINUTES`, `JWT_REFRESH_TTL_DAYS`
+///
+/// Sin `JWT_KEYS_DIR` se genera una clave efímera, salvo en producción (`APP_ENV=production`).
 pub struct JwtService {
-    encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    keys: HashMap<String, JwtKey>,
+    active_kid: String,
+    access_ttl: Duration,
+    refresh_ttl: Duration,
 }
 
 impl JwtService {
-    pub fn new() -> Self {
-        let secret = std::env::var("JWT_SECRET")
-            .unwrap_or_else(|_| "pitaia-super-secret-key-change-in-production".to_string());
-        
-        Self {
-            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
-            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+    pub fn from_env() -> Result<Self> {
+        let mut keys = match std::env::var("JWT_KEYS_DIR") {
+            Ok(dir) => keys::load_dir(Path::new(&dir))?,
+            Err(_) => Vec::new(),
+        };
+
+        let active_kid = match std::env::var("JWT_ACTIVE_KID") {
+            Ok(kid) => kid,
+            Err(_) => match keys.last() {
+                Some(key) => key.kid.clone(),
+                None if is_production() => return Err(anyhow::anyhow!(
+                    "JWT_KEYS_DIR debe contener al menos una clave RSA o Ed25519 en producción"
+                )),
+                None => {
+                    let kid = format!("dev-{}", &generate_opaque_token()[..8]);
+                    tracing::warn!("JWT_KEYS_DIR no configurado: usando clave efímera {} (los tokens no sobreviven a un reinicio)", kid);
+                    keys.push(JwtKey::ephemeral(&kid)?);
+                    kid
+                }
+            },
+        };
+
+        if let Ok(secret) = std::env::var("JWT_SECRET") {
+            if is_production() && secret.len() < 32 {
+                return Err(anyhow::anyhow!("JWT_SECRET debe tener al menos 32 caracteres en producción"));
+            }
+            keys.push(JwtKey::hmac(LEGACY_KID, secret.as_bytes()));
+        }
+
+        Self::new(
+            keys,
+            &active_kid,
+            Duration::minutes(env_i64("JWT_ACCESS_TTL_MINUTES", 15)),
+            Duration::days(env_i64("JWT_REFRESH_TTL_DAYS", 30)),
+        )
+    }
+
+    pub fn new(keys: Vec<JwtKey>, active_kid: &str, access_ttl: Duration, refresh_ttl: Duration) -> Result<Self> {
+        let keys: HashMap<String, JwtKey> = keys.into_iter()
+            .map(|key| (key.kid.clone(), key))
+            .collect();
+
+        match keys.get(active_kid) {
+            Some(key) if key.jwk.is_some() => {},
+            Some(_) => return Err(anyhow::anyhow!("La clave activa {} debe ser asimétrica", active_kid)),
+            None => return Err(anyhow::anyhow!("JWT_ACTIVE_KID {} no existe en JWT_KEYS_DIR", active_kid)),
         }
+
+        Ok(Self {
+            keys,
+            active_kid: active_kid.to_string(),
+            access_ttl,
+            refresh_ttl,
+        })
+    }
+
+    pub fn access_ttl(&self) -> Duration {
+        self.access_ttl
+    }
+
+    pub fn refresh_ttl(&self) -> Duration {
+        self.refresh_ttl
     }
-    
-    pub fn generate_token(&self, user_id: Uuid, username: &str) -> Result<String> {
+
+    /// Claves públicas vigentes, para `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> JwkSet {
+        let mut keys: Vec<_> = self.keys.values()
+            .filter_map(|key| key.jwk.clone())
+            .collect();
+        keys.sort_by(|a, b| a.common.key_id.cmp(&b.common.key_id));
+
+        JwkSet { keys }
+    }
+
+    pub fn generate_token(&self, user: &User, session_id: Uuid, roles: &[Role]) -> Result<String> {
+        let key = &self.keys[&self.active_kid];
         let now = Utc::now();
         let claims = Claims {
-            sub: user_id.to_string(),
-            username: username.to_string(),
-            exp: (now + Duration::hours(24)).timestamp() as usize,
+            sub: user.id.to_string(),
+            username: user.username.clone(),
+            sid: session_id.to_string(),
+            roles: roles.to_vec(),
+            ver: user.token_version,
+            exp: (now + self.access_ttl).timestamp() as usize,
             iat: now.timestamp() as usize,
         };
-        
-        encode(&Header::default(), &claims, &self.encoding_key)
+
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+
+        encode(&header, &claims, &key.encoding_key)
             .map_err(|e| anyhow::anyhow!("Error generando token: {}", e))
     }
-    
+
     pub fn verify_token(&self, token: &str) -> Result<Claims> {
-        decode::<Claims>(token, &self.decoding_key, &Validation::default())
+        let header = decode_header(token)
+            .map_err(|e| anyhow::anyhow!("Token inválido: {}", e))?;
+        let kid = header.kid.as_deref().unwrap_or(LEGACY_KID);
+        let key = self.keys.get(kid)
+            .ok_or_else(|| anyhow::anyhow!("Token firmado con una clave desconocida: {}", kid))?;
+
+        // El algoritmo lo fija la clave, nunca la cabecera del token
+        decode::<Claims>(token, &key.decoding_key, &Validation::new(key.algorithm))
             .map(|data| data.claims)
             .map_err(|e| anyhow::anyhow!("Token inválido: {}", e))
     }
 }
 
-pub fn hash_password(password: &str) -> Result<String> {
-    bcrypt::hash(password, bcrypt::DEFAULT_COST)
-        .map_err(|e| anyhow::anyhow!("Error hasheando password: {}", e))
+/// `APP_ENV=production` activa las comprobaciones estrictas de configuración.
+pub fn is_production() -> bool {
+    std::env::var("APP_ENV").map(|env| env == "production").unwrap_or(false)
+}
+
+fn env_i64(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Genera un token opaco aleatorio (256 bits, hex) para refresh tokens.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Prefijo de los tokens de acceso personal, para distinguirlos de los JWT.
+pub const ACCESS_TOKEN_PREFIX: &str = "pat_";
+
+pub fn generate_access_token() -> String {
+    format!("{}{}", ACCESS_TOKEN_PREFIX, generate_opaque_token())
+}
+
+/// Hash SHA-256 de un token opaco; sólo el hash se guarda en la base de datos.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub async fn hash_password(password: &str) -> Result<String> {
+    password::hash_blocking(password).await
+}
+
+pub async fn verify_password(password: &str, hash: &str) -> Result<bool> {
+    password::verify_blocking(password, hash).await
 }
 
-pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
-    bcrypt::verify(password, hash)
-        .map_err(|e| anyhow::anyhow!("Error verificando password: {}", e))
+pub fn password_needs_rehash(hash: &str) -> bool {
+    password::hasher().needs_rehash(hash)
 }
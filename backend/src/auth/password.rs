@@ -0,0 +1,154 @@
+use anyhow::Result;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Argon2id,
+    Bcrypt,
+}
+
+/// Hashea contraseñas con el algoritmo configurado y verifica también hashes antiguos,
+/// indicando cuándo conviene re-hashear con los parámetros actuales.
+///
+/// Configuración por entorno:
+/// - `PASSWORD_HASH_ALGORITHM`: `argon2id` (por defecto) o `bcrypt`
+/// - `ARGON2_MEMORY_KIB`, `ARGON2_ITERATIONS`, `ARGON2_PARALLELISM`
+/// - `BCRYPT_COST`
+pub struct PasswordHasher {
+    algorithm: HashAlgorithm,
+    argon2_params: Params,
+    bcrypt_cost: u32,
+}
+
+impl PasswordHasher {
+    pub fn from_env() -> Result<Self> {
+        let algorithm = match std::env::var("PASSWORD_HASH_ALGORITHM")
+            .unwrap_or_else(|_| "argon2id".to_string())
+            .as_str()
+        {
+            "argon2id" => HashAlgorithm::Argon2id,
+            "bcrypt" => HashAlgorithm::Bcrypt,
+            other => return Err(anyhow::anyhow!("PASSWORD_HASH_ALGORITHM desconocido: {}", other)),
+        };
+
+        // Valores por defecto recomendados por OWASP para Argon2id
+        let argon2_params = Params::new(
+            env_u32("ARGON2_MEMORY_KIB", 19 * 1024),
+            env_u32("ARGON2_ITERATIONS", 2),
+            env_u32("ARGON2_PARALLELISM", 1),
+            None,
+        )
+        .map_err(|e| anyhow::anyhow!("Parámetros de Argon2 inválidos: {}", e))?;
+
+        Ok(Self {
+            algorithm,
+            argon2_params,
+            bcrypt_cost: env_u32("BCRYPT_COST", bcrypt::DEFAULT_COST),
+        })
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String> {
+        match self.algorithm {
+            HashAlgorithm::Argon2id => {
+                let salt = SaltString::generate(&mut OsRng);
+                self.argon2()
+                    .hash_password(password.as_bytes(), &salt)
+                    .map(|hash| hash.to_string())
+                    .map_err(|e| anyhow::anyhow!("Error hasheando password: {}", e))
+            }
+            HashAlgorithm::Bcrypt => bcrypt::hash(password, self.bcrypt_cost)
+                .map_err(|e| anyhow::anyhow!("Error hasheando password: {}", e)),
+        }
+    }
+
+    /// Verifica contra cualquier algoritmo soportado, detectado por el prefijo del hash.
+    /// Un hash mal formado se trata como no coincidente.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        match detect_algorithm(hash) {
+            Some(HashAlgorithm::Argon2id) => {
+                let parsed = match PasswordHash::new(hash) {
+                    Ok(parsed) => parsed,
+                    Err(_) => return Ok(false),
+                };
+                // Los parámetros vienen en el propio hash, así que sirven los de cualquier instancia
+                Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+            }
+            Some(HashAlgorithm::Bcrypt) => Ok(bcrypt::verify(password, hash).unwrap_or(false)),
+            None => Ok(false),
+        }
+    }
+
+    /// Indica si el hash usa otro algoritmo o parámetros distintos de los actuales.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        match (self.algorithm, detect_algorithm(hash)) {
+            (HashAlgorithm::Argon2id, Some(HashAlgorithm::Argon2id)) => {
+                let Ok(parsed) = PasswordHash::new(hash) else {
+                    return true;
+                };
+                let Ok(params) = Params::try_from(&parsed) else {
+                    return true;
+                };
+
+                parsed.version != Some(Version::V0x13.into())
+                    || params.m_cost() != self.argon2_params.m_cost()
+                    || params.t_cost() != self.argon2_params.t_cost()
+                    || params.p_cost() != self.argon2_params.p_cost()
+            }
+            (HashAlgorithm::Bcrypt, Some(HashAlgorithm::Bcrypt)) => {
+                bcrypt_cost(hash).map_or(true, |cost| cost != self.bcrypt_cost)
+            }
+            _ => true,
+        }
+    }
+
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.argon2_params.clone())
+    }
+}
+
+/// Instancia global, construida a partir del entorno la primera vez que se usa.
+pub fn hasher() -> &'static PasswordHasher {
+    static HASHER: OnceLock<PasswordHasher> = OnceLock::new();
+    HASHER.get_or_init(|| {
+        PasswordHasher::from_env().expect("Configuración de hashing de contraseñas inválida")
+    })
+}
+
+/// Hashea en el pool de tareas bloqueantes: el KDF es costoso y no debe ocupar los hilos
+/// del runtime.
+pub async fn hash_blocking(password: &str) -> Result<String> {
+    let password = password.to_string();
+    tokio::task::spawn_blocking(move || hasher().hash(&password)).await?
+}
+
+/// Verifica en el pool de tareas bloqueantes, igual que `hash_blocking`.
+pub async fn verify_blocking(password: &str, hash: &str) -> Result<bool> {
+    let (password, hash) = (password.to_string(), hash.to_string());
+    tokio::task::spawn_blocking(move || hasher().verify(&password, &hash)).await?
+}
+
+fn detect_algorithm(hash: &str) -> Option<HashAlgorithm> {
+    if hash.starts_with("$argon2id$") {
+        Some(HashAlgorithm::Argon2id)
+    } else if ["$2a$", "$2b$", "$2x$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix)) {
+        Some(HashAlgorithm::Bcrypt)
+    } else {
+        None
+    }
+}
+
+// Formato bcrypt: $2b$<coste>$<salt+hash>
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    hash.split('$').nth(2)?.parse().ok()
+}
+
+fn env_u32(name: &str, default: u32) -> u32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
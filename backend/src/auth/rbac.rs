@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+/// Roles de usuario. Todos tienen `User`; los demás se asignan desde la administración.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+/// Permisos concretos que los handlers exigen; los roles son conjuntos de permisos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Permission {
+    ModeratePosts,
+    SuspendUsers,
+    ViewSecurityLog,
+    ManageRoles,
+    VerifyAccounts,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Role> {
+        match value {
+            "user" => Some(Role::User),
+            "moderator" => Some(Role::Moderator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::User => &[],
+            Role::Moderator => &[Permission::ModeratePosts, Permission::SuspendUsers],
+            Role::Admin => &[
+                Permission::ModeratePosts,
+                Permission::SuspendUsers,
+                Permission::ViewSecurityLog,
+                Permission::ManageRoles,
+                Permission::VerifyAccounts,
+            ],
+        }
+    }
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ModeratePosts => "posts:moderate",
+            Permission::SuspendUsers => "users:suspend",
+            Permission::ViewSecurityLog => "security:read",
+            Permission::ManageRoles => "roles:manage",
+            Permission::VerifyAccounts => "users:verify",
+        }
+    }
+}
+
+pub fn has_permission(roles: &[Role], permission: Permission) -> bool {
+    roles.iter().any(|role| role.permissions().contains(&permission))
+}
+
+/// Permiso exigido a nivel de tipo por el extractor `RequirePermission<P>`.
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_markers {
+    ($($name:ident),* $(,)?) => {
+        $(
+            pub struct $name;
+
+            impl RequiredPermission for $name {
+                const PERMISSION: Permission = Permission::$name;
+            }
+        )*
+    };
+}
+
+/// Marcadores para declarar permisos en los handlers: `RequirePermission<perm::ManageRoles>`.
+pub mod perm {
+    use super::{Permission, RequiredPermission};
+
+    permission_markers!(SuspendUsers, ViewSecurityLog, ManageRoles, VerifyAccounts);
+}
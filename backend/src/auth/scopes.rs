@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// Scopes que se pueden conceder a un token de acceso personal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "posts:read")]
+    PostsRead,
+    #[serde(rename = "posts:write")]
+    PostsWrite,
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "profile:write")]
+    ProfileWrite,
+    #[serde(rename = "dm:read")]
+    DmRead,
+    #[serde(rename = "dm:write")]
+    DmWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::PostsRead,
+        Scope::PostsWrite,
+        Scope::ProfileRead,
+        Scope::ProfileWrite,
+        Scope::DmRead,
+        Scope::DmWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::PostsRead => "posts:read",
+            Scope::PostsWrite => "posts:write",
+            Scope::ProfileRead => "profile:read",
+            Scope::ProfileWrite => "profile:write",
+            Scope::DmRead => "dm:read",
+            Scope::DmWrite => "dm:write",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == value)
+    }
+}
+
+/// Scope que exige una ruta a los tokens de acceso personal.
+/// Se declara en el router con `.layer(Extension(RequiredScope(Scope::PostsWrite)))`;
+/// las rutas sin scope declarado no aceptan tokens personales, sólo sesiones.
+#[derive(Debug, Clone, Copy)]
+pub struct RequiredScope(pub Scope);
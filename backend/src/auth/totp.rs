@@ -0,0 +1,70 @@
+use anyhow::Result;
+use rand::{Rng, RngCore};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+const ISSUER: &str = "Pitaia";
+const STEP_SECONDS: u64 = 30;
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Genera un secreto TOTP de 160 bits codificado en base32.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    match Secret::Raw(bytes.to_vec()).to_encoded() {
+        Secret::Encoded(encoded) => encoded,
+        Secret::Raw(_) => unreachable!(),
+    }
+}
+
+pub fn build(secret: &str, account_name: &str) -> Result<TOTP> {
+    let bytes = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| anyhow::anyhow!("Secreto TOTP inválido: {:?}", e))?;
+
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        STEP_SECONDS,
+        bytes,
+        Some(ISSUER.to_string()),
+        account_name.to_string(),
+    )
+    .map_err(|e| anyhow::anyhow!("Error configurando TOTP: {}", e))
+}
+
+/// Comprueba un código admitiendo un paso de desfase y devuelve el paso de tiempo que coincidió,
+/// para que el llamador pueda impedir que el mismo código se use dos veces.
+pub fn verify_code(totp: &TOTP, code: &str) -> Option<i64> {
+    let code = code.trim();
+    let now = chrono::Utc::now().timestamp() as u64;
+    let current_step = now / STEP_SECONDS;
+
+    [current_step.saturating_sub(1), current_step, current_step + 1]
+        .into_iter()
+        .find(|step| totp.generate(step * STEP_SECONDS) == code)
+        .map(|step| step as i64)
+}
+
+/// Genera códigos de recuperación legibles con el formato `xxxxx-xxxxx`.
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..count)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+/// Normaliza un código de recuperación tal como lo escribe el usuario (mayúsculas, guiones, espacios).
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,219 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::{generate_opaque_token, hash_token};
+use crate::handlers::api_url;
+use crate::mailer::{EmailMessage, Mailer};
+use crate::media::ProfileImage;
+use crate::models::DataExport;
+use crate::repository::{ExportRepository, UserRepository};
+use crate::storage::Storage;
+
+/// Genera en segundo plano los paquetes de datos personales solicitados y borra los caducados.
+///
+/// Configuración por entorno:
+/// - `EXPORT_DIR`: directorio de los paquetes (por defecto `./exports`)
+/// - `EXPORT_TTL_DAYS`: días que el enlace de descarga sigue activo (3 por defecto)
+/// - `EXPORT_POLL_SECONDS`: cada cuánto se buscan trabajos pendientes (30 por defecto)
+/// - `EXPORT_STALE_MINUTES`: minutos en proceso tras los que una exportación se da por
+///   interrumpida y vuelve a la cola (30 por defecto)
+pub struct ExportWorker {
+    export_repo: Arc<ExportRepository>,
+    user_repo: Arc<UserRepository>,
+    mailer: Arc<dyn Mailer>,
+    storage: Arc<dyn Storage>,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ExportWorker {
+    pub fn from_env(
+        export_repo: Arc<ExportRepository>,
+        user_repo: Arc<UserRepository>,
+        mailer: Arc<dyn Mailer>,
+        storage: Arc<dyn Storage>,
+    ) -> Self {
+        let dir = std::env::var("EXPORT_DIR").unwrap_or_else(|_| "./exports".to_string());
+
+        Self {
+            export_repo,
+            user_repo,
+            mailer,
+            storage,
+            dir: PathBuf::from(dir),
+            ttl: Duration::days(env_i64("EXPORT_TTL_DAYS", 3)),
+        }
+    }
+
+    pub fn spawn(self) {
+        let seconds = env_i64("EXPORT_POLL_SECONDS", 30).max(1) as u64;
+
+        tokio::spawn(async move {
+            // Lo que quedó a medias en un reinicio vuelve a la cola; lo reciente puede
+            // seguir en marcha en otra instancia
+            let stale_before = Utc::now() - Duration::minutes(env_i64("EXPORT_STALE_MINUTES", 30).max(1));
+            if let Err(e) = self.export_repo.requeue_stale(stale_before).await {
+                tracing::error!("Error recuperando exportaciones interrumpidas: {}", e);
+            }
+
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(seconds));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.run_once().await {
+                    tracing::error!("Error procesando exportaciones de datos: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn run_once(&self) -> Result<()> {
+        for export in self.export_repo.expire_due().await? {
+            if let Some(path) = export.file_path {
+                if let Err(e) = tokio::fs::remove_file(&path).await {
+                    tracing::warn!("No se pudo borrar la exportación caducada {}: {}", path, e);
+                }
+            }
+        }
+
+        while let Some(export) = self.export_repo.claim_next().await? {
+            if let Err(e) = self.process(&export).await {
+                tracing::error!("Exportación {} fallida: {}", export.id, e);
+                self.export_repo.mark_failed(export.id, &e.to_string()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process(&self, export: &DataExport) -> Result<()> {
+        let sections = self.export_repo.personal_data(export.user_id).await?;
+        let media = self.collect_media(export.user_id).await?;
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join(format!("{}.zip", export.id));
+        let archive_path = path.clone();
+
+        // Escribir el zip es bloqueante; se hace fuera del runtime
+        let size_bytes = tokio::task::spawn_blocking(move || write_archive(&archive_path, &sections, &media)).await??;
+
+        let token = generate_opaque_token();
+        let expires_at = Utc::now() + self.ttl;
+        let marked = self.export_repo
+            .mark_ready(export.id, &path.to_string_lossy(), size_bytes as i64, &hash_token(&token), expires_at)
+            .await?;
+
+        // La cuenta se purgó mientras se generaba: el paquete no debe quedarse en disco
+        if !marked {
+            tokio::fs::remove_file(&path).await?;
+            return Ok(());
+        }
+
+        // Avisar al usuario (las cuentas desactivadas no reciben el aviso)
+        if let Some(user) = self.user_repo.find_by_id(export.user_id).await? {
+            let message = EmailMessage {
+                to: user.email,
+                subject: "Tu copia de datos de Pitaia está lista".to_string(),
+                body: format!(
+                    "Hola {},\n\nYa puedes descargar la copia de tus datos (disponible hasta el {}):\n{}/api/exports/{}\n",
+                    user.username,
+                    expires_at.format("%d/%m/%Y %H:%M UTC"),
+                    api_url(),
+                    token
+                ),
+            };
+
+            if let Err(e) = self.mailer.send(message).await {
+                tracing::error!("Error enviando aviso de exportación: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Avatar, portada e imágenes de los posts, con su ruta dentro del paquete
+    async fn collect_media(&self, user_id: Uuid) -> Result<Vec<(String, Vec<u8>)>> {
+        let media = self.export_repo.media(user_id).await?;
+        let mut keys = Vec::new();
+
+        for (folder, kind, key) in [
+            ("avatar", ProfileImage::Avatar, media.avatar_key),
+            ("banner", ProfileImage::Banner, media.banner_key),
+        ] {
+            if let Some(key) = key {
+                for (name, _, _) in kind.variants() {
+                    keys.push((format!("{}/{}.webp", key, name), format!("media/{}/{}.webp", folder, name)));
+                }
+            }
+        }
+
+        // Las imágenes enlazadas desde fuera no están en nuestro almacenamiento
+        for (post_id, url) in media.post_images {
+            if let Some(key) = self.storage.key_for_url(&url) {
+                let file_name = match Path::new(&key).extension() {
+                    Some(extension) => format!("media/posts/{}.{}", post_id, extension.to_string_lossy()),
+                    None => format!("media/posts/{}", post_id),
+                };
+                keys.push((key, file_name));
+            }
+        }
+
+        let mut files = Vec::with_capacity(keys.len());
+        for (key, file_name) in keys {
+            // Un archivo que falta o no se puede leer no debe tumbar toda la exportación
+            match self.storage.get(&key).await {
+                Ok(Some(data)) => files.push((file_name, data)),
+                Ok(None) => tracing::warn!("Exportación de {}: falta el archivo {}", user_id, key),
+                Err(e) => tracing::warn!("Exportación de {}: no se pudo leer {}: {}", user_id, key, e),
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+fn write_archive(
+    path: &Path,
+    sections: &[(&'static str, serde_json::Value)],
+    media: &[(String, Vec<u8>)],
+) -> Result<u64> {
+    let file = std::fs::File::create(path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("README.txt", options)?;
+    zip.write_all(
+        "Copia de tus datos en Pitaia.\n\nCada archivo JSON contiene una sección: perfil, posts, likes, \
+         seguidores, seguidos, sesiones, cuentas vinculadas, bloqueadas, silenciadas y \
+         solicitudes de verificación. La carpeta media/ contiene tu avatar, tu portada y las \
+         imágenes de tus posts.\n".as_bytes()
+    )?;
+
+    for (name, data) in sections {
+        zip.start_file(format!("{}.json", name), options)?;
+        zip.write_all(&serde_json::to_vec_pretty(data)?)?;
+    }
+
+    // Las imágenes ya vienen comprimidas
+    let stored = options.compression_method(zip::CompressionMethod::Stored);
+    for (name, data) in media {
+        zip.start_file(name.as_str(), stored)?;
+        zip.write_all(data)?;
+    }
+
+    let file = zip.finish()?;
+    Ok(file.metadata()?.len())
+}
+
+fn env_i64(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,129 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::auth::scopes::Scope;
+use crate::auth::{generate_access_token, hash_token, AccountStateCache};
+use crate::models::{AccessTokenInfo, ApiResponse, CreateAccessTokenRequest, CreatedAccessToken};
+use crate::repository::AccessTokenRepository;
+use crate::middleware::AuthUser;
+
+const MAX_ACTIVE_TOKENS: i64 = 25;
+const DEFAULT_EXPIRY_DAYS: i64 = 90;
+
+pub async fn list_tokens(
+    State(access_token_repo): State<Arc<AccessTokenRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let tokens = match access_token_repo.list_active(auth_user.id).await {
+        Ok(tokens) => tokens,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los tokens"))
+        ))
+    };
+
+    let tokens: Vec<AccessTokenInfo> = tokens.into_iter().map(AccessTokenInfo::from).collect();
+    Ok(Json(ApiResponse::success(tokens, "Tokens obtenidos exitosamente")))
+}
+
+pub async fn create_token(
+    State(access_token_repo): State<Arc<AccessTokenRepository>>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateAccessTokenRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    // Normalizar scopes: sólo valores conocidos, sin duplicados
+    let mut scopes: Vec<String> = Vec::new();
+    for value in &payload.scopes {
+        let Some(scope) = Scope::parse(value) else {
+            let allowed: Vec<&str> = Scope::ALL.iter().map(|scope| scope.as_str()).collect();
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(&format!(
+                    "Scope desconocido: {}. Valores permitidos: {}",
+                    value,
+                    allowed.join(", ")
+                )))
+            ));
+        };
+        if !scopes.iter().any(|existing| existing == scope.as_str()) {
+            scopes.push(scope.as_str().to_string());
+        }
+    }
+
+    match access_token_repo.count_active(auth_user.id).await {
+        Ok(count) if count >= MAX_ACTIVE_TOKENS => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error(&format!("No puedes tener más de {} tokens activos", MAX_ACTIVE_TOKENS)))
+        )),
+        Ok(_) => {},
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    let token = generate_access_token();
+    let token_prefix: String = token.chars().take(12).collect();
+    let expires_at = Utc::now() + Duration::days(payload.expires_in_days.unwrap_or(DEFAULT_EXPIRY_DAYS));
+
+    let access_token = match access_token_repo.create(
+        auth_user.id,
+        payload.name.trim(),
+        &hash_token(&token),
+        &token_prefix,
+        &scopes,
+        expires_at,
+    ).await {
+        Ok(access_token) => access_token,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al crear el token"))
+        ))
+    };
+
+    let created = CreatedAccessToken {
+        token,
+        access_token: access_token.into(),
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(created, "Token creado. Guárdalo ahora: no se volverá a mostrar"))
+    ))
+}
+
+pub async fn revoke_token(
+    State(access_token_repo): State<Arc<AccessTokenRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    Path(token_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match access_token_repo.revoke(token_id, auth_user.id).await {
+        Ok(true) => {
+            account_cache.invalidate_access_token(token_id);
+            Ok(Json(ApiResponse::success((), "Token revocado")))
+        },
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Token no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al revocar el token"))
+        ))
+    }
+}
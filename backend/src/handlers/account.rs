@@ -0,0 +1,297 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{
+    is_reserved_username, AccountDeletion, ApiResponse, ChangeEmailRequest, ChangePasswordRequest,
+    ChangeUsernameRequest, DeleteAccountRequest, User, UserProfile,
+};
+use crate::auth::{hash_password, verify_password, AccountStateCache};
+use crate::mailer::{EmailMessage, Mailer};
+use crate::repository::{EmailVerificationRepository, UserRepository};
+use crate::middleware::{AuthUser, ClientInfo};
+use crate::throttle::{LoginBlock, LoginGuard};
+use crate::users::deletion_grace_days;
+use super::auth::login_block_response;
+use super::verification::send_email_change_confirmation;
+
+pub async fn change_password(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    State(login_guard): State<Arc<LoginGuard>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = find_current_user(&user_repo, &auth_user).await?;
+    check_current_password(&login_guard, &user, &payload.current_password, &client).await?;
+
+    let password_hash = match hash_password(&payload.new_password).await {
+        Ok(hash) => hash,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // La sesión actual sigue abierta; el resto de sesiones y los tokens personales se revocan
+    if user_repo.change_password(user.id, &password_hash, auth_user.session_id).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al cambiar la contraseña"))
+        ));
+    }
+    account_cache.invalidate_user(user.id);
+
+    Ok(Json(ApiResponse::success((), "Contraseña actualizada. Se cerraron las demás sesiones")))
+}
+
+pub async fn change_email(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(email_verification_repo): State<Arc<EmailVerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    State(login_guard): State<Arc<LoginGuard>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangeEmailRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = find_current_user(&user_repo, &auth_user).await?;
+    check_current_password(&login_guard, &user, &payload.current_password, &client).await?;
+
+    let new_email = payload.new_email.trim();
+    if new_email.eq_ignore_ascii_case(&user.email) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("El nuevo email es igual al actual"))
+        ));
+    }
+
+    if let Ok(Some(_)) = user_repo.find_by_email(new_email).await {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El email ya está registrado"))
+        ));
+    }
+
+    // El email de la cuenta no cambia hasta que se confirme desde la dirección nueva
+    if send_email_change_confirmation(&email_verification_repo, mailer, &user, new_email).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al enviar el correo de confirmación"))
+        ));
+    }
+
+    Ok(Json(ApiResponse::success((), "Te enviamos un enlace al nuevo email para confirmar el cambio")))
+}
+
+pub async fn change_username(
+    State(user_repo): State<Arc<UserRepository>>,
+    auth_user: AuthUser,
+    Json(payload): Json<ChangeUsernameRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = find_current_user(&user_repo, &auth_user).await?;
+    let username = payload.username.trim();
+
+    if username == user.username {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("El nuevo nombre de usuario es igual al actual"))
+        ));
+    }
+
+    if is_reserved_username(username) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Ese nombre de usuario está reservado"))
+        ));
+    }
+
+    if let Some(changed_at) = user.username_changed_at {
+        let available_at = changed_at + Duration::days(username_change_cooldown_days());
+        if available_at > Utc::now() {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::error(&format!(
+                    "Podrás volver a cambiar tu nombre de usuario a partir del {}",
+                    available_at.format("%d/%m/%Y")
+                )))
+            ));
+        }
+    }
+
+    // Los nombres abandonados quedan retenidos mientras redirigen a su nuevo dueño
+    let since = Utc::now() - username_grace_period();
+    match user_repo.is_username_available(username, Some(user.id), since).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El nombre de usuario ya está en uso"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    // Cambiar de nombre retira la verificación
+    let was_verified = user.is_verified;
+    let user = match user_repo.change_username(user.id, username).await {
+        Ok(user) => user,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al cambiar el nombre de usuario"))
+        ))
+    };
+
+    let user_profile: UserProfile = user.into();
+    let message = if was_verified {
+        "Nombre de usuario actualizado. Tu cuenta ha dejado de estar verificada"
+    } else {
+        "Nombre de usuario actualizado"
+    };
+    Ok(Json(ApiResponse::success(user_profile, message)))
+}
+
+pub async fn delete_account(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    State(login_guard): State<Arc<LoginGuard>>,
+    auth_user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = find_current_user(&user_repo, &auth_user).await?;
+    check_current_password(&login_guard, &user, &payload.current_password, &client).await?;
+
+    // La cuenta deja de ser visible ya; los datos se borran al terminar el periodo de gracia
+    let deletion_scheduled_at = Utc::now() + Duration::days(deletion_grace_days());
+
+    if user_repo.schedule_deletion(user.id, deletion_scheduled_at).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al eliminar la cuenta"))
+        ));
+    }
+    account_cache.invalidate_user(user.id);
+
+    let message = EmailMessage {
+        to: user.email,
+        subject: "Tu cuenta de Pitaia se eliminará".to_string(),
+        body: format!(
+            "Hola {},\n\nHemos desactivado tu cuenta y la eliminaremos definitivamente el {}.\n\nSi cambias de opinión, inicia sesión antes de esa fecha y la eliminación se cancelará.\n",
+            user.username,
+            deletion_scheduled_at.format("%d/%m/%Y")
+        ),
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Error enviando aviso de eliminación de cuenta: {}", e);
+        }
+    });
+
+    Ok(Json(ApiResponse::success(
+        AccountDeletion { deletion_scheduled_at },
+        "Cuenta desactivada. Inicia sesión antes de la fecha indicada para cancelar la eliminación"
+    )))
+}
+
+/// Tiempo durante el que un nombre antiguo redirige al nuevo y nadie más puede tomarlo.
+pub(crate) fn username_grace_period() -> Duration {
+    let days = std::env::var("USERNAME_REDIRECT_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(30);
+
+    Duration::days(days)
+}
+
+fn username_change_cooldown_days() -> i64 {
+    std::env::var("USERNAME_CHANGE_COOLDOWN_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(30)
+}
+
+async fn find_current_user(
+    user_repo: &UserRepository,
+    auth_user: &AuthUser,
+) -> Result<User, (StatusCode, Json<ApiResponse<()>>)> {
+    match user_repo.find_by_id(auth_user.id).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+// Comprueba la contraseña actual con los mismos límites que el login: con un token robado
+// no debe poder probarse contraseñas sin freno
+async fn check_current_password(
+    login_guard: &LoginGuard,
+    user: &User,
+    password: &str,
+    client: &ClientInfo,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let ip_address = client.ip_address.as_deref();
+
+    match login_guard.check(&user.username, ip_address).await {
+        Ok(Some(block)) => return Err(login_block_response(block)),
+        Ok(None) => {},
+        Err(e) => tracing::error!("Error consultando intentos de login: {}", e),
+    }
+
+    if verify_password(password, &user.password_hash).await.unwrap_or(false) {
+        if let Err(e) = login_guard.record_success(&user.username).await {
+            tracing::error!("Error reiniciando intentos de login: {}", e);
+        }
+        return Ok(());
+    }
+
+    match login_guard.record_failure(&user.username, ip_address, Some(user.id)).await {
+        Ok(Some(block @ LoginBlock::AccountLocked { .. })) => Err(login_block_response(block)),
+        Ok(_) => Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("La contraseña actual no es correcta"))
+        )),
+        Err(e) => {
+            tracing::error!("Error registrando intento de login fallido: {}", e);
+            Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("La contraseña actual no es correcta"))
+            ))
+        }
+    }
+}
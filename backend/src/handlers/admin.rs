@@ -0,0 +1,425 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::rbac::{perm, Role};
+use crate::auth::AccountStateCache;
+use crate::mailer::{EmailMessage, Mailer};
+use crate::models::{
+    ApiResponse, BanUserRequest, GrantRoleRequest, ReviewVerificationRequest, RevokeVerificationRequest,
+    User, UserRoles, VerificationRequest,
+};
+use crate::repository::{
+    LoginLockoutRepository, RoleRepository, RoleRevocation, UserRepository, VerificationRepository,
+};
+use crate::middleware::RequirePermission;
+
+#[derive(Deserialize)]
+pub struct LockoutsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub async fn list_user_roles(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    _admin: RequirePermission<perm::ManageRoles>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = find_user(&user_repo, &username).await?;
+
+    let roles = match role_repo.list_assignments(user.id).await {
+        Ok(roles) => roles,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los roles"))
+        ))
+    };
+
+    let user_roles = UserRoles {
+        user_id: user.id,
+        username: user.username,
+        roles,
+    };
+
+    Ok(Json(ApiResponse::success(user_roles, "Roles obtenidos exitosamente")))
+}
+
+pub async fn grant_role(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    admin: RequirePermission<perm::ManageRoles>,
+    Path(username): Path<String>,
+    Json(payload): Json<GrantRoleRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let role = parse_assignable_role(&payload.role)?;
+    let user = find_user(&user_repo, &username).await?;
+
+    match role_repo.grant(user.id, role, admin.user.id).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El usuario ya tiene ese rol"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al asignar el rol"))
+        ))
+    }
+
+    tracing::info!("{} asignó el rol {} a {}", admin.user.username, role.as_str(), user.username);
+    Ok(Json(ApiResponse::success((), "Rol asignado. Se aplicará cuando el usuario renueve su token")))
+}
+
+pub async fn revoke_role(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    admin: RequirePermission<perm::ManageRoles>,
+    Path((username, role)): Path<(String, String)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let role = parse_assignable_role(&role)?;
+    let user = find_user(&user_repo, &username).await?;
+
+    // Nunca dejar la plataforma sin administradores
+    match role_repo.revoke(user.id, role, admin.user.id).await {
+        Ok(RoleRevocation::Revoked) => {},
+        Ok(RoleRevocation::NotAssigned) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("El usuario no tiene ese rol"))
+        )),
+        Ok(RoleRevocation::LastAdmin) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("No se puede revocar el rol al último administrador"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al revocar el rol"))
+        ))
+    }
+    account_cache.invalidate_user(user.id);
+
+    tracing::info!("{} revocó el rol {} a {}", admin.user.username, role.as_str(), user.username);
+    Ok(Json(ApiResponse::success((), "Rol revocado")))
+}
+
+pub async fn ban_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    moderator: RequirePermission<perm::SuspendUsers>,
+    Path(username): Path<String>,
+    Json(payload): Json<BanUserRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = find_user(&user_repo, &username).await?;
+
+    if user.id == moderator.user.id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("No puedes suspender tu propia cuenta"))
+        ));
+    }
+
+    // El personal sólo puede ser suspendido por un administrador
+    let target_roles = match role_repo.roles_for_user(user.id).await {
+        Ok(roles) => roles,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+    if target_roles.iter().any(|role| *role != Role::User) && !moderator.user.roles.contains(&Role::Admin) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Sólo un administrador puede suspender a moderadores o administradores"))
+        ));
+    }
+
+    if user_repo.ban(user.id, payload.reason.as_deref(), payload.until).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al suspender la cuenta"))
+        ));
+    }
+    account_cache.invalidate_user(user.id);
+
+    tracing::info!("{} suspendió la cuenta de {}", moderator.user.username, user.username);
+    Ok(Json(ApiResponse::success((), "Cuenta suspendida")))
+}
+
+pub async fn unban_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    moderator: RequirePermission<perm::SuspendUsers>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = find_user(&user_repo, &username).await?;
+
+    match user_repo.unban(user.id).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("La cuenta no está suspendida"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al levantar la suspensión"))
+        ))
+    }
+    account_cache.invalidate_user(user.id);
+
+    tracing::info!("{} levantó la suspensión de {}", moderator.user.username, user.username);
+    Ok(Json(ApiResponse::success((), "Suspensión levantada")))
+}
+
+pub async fn list_lockouts(
+    State(login_lockout_repo): State<Arc<LoginLockoutRepository>>,
+    _admin: RequirePermission<perm::ViewSecurityLog>,
+    Query(params): Query<LockoutsQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let limit = params.limit.unwrap_or(50).min(200);
+    let offset = params.offset.unwrap_or(0);
+
+    let lockouts = match login_lockout_repo.list_recent(limit, offset).await {
+        Ok(lockouts) => lockouts,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los bloqueos"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(lockouts, "Bloqueos obtenidos exitosamente")))
+}
+
+#[derive(Deserialize)]
+pub struct VerificationQueueQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub async fn list_verification_requests(
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    _admin: RequirePermission<perm::VerifyAccounts>,
+    Query(params): Query<VerificationQueueQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let status = params.status.as_deref().unwrap_or("pending");
+    if !matches!(status, "pending" | "approved" | "rejected" | "cancelled") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Estado inválido. Valores permitidos: pending, approved, rejected, cancelled"))
+        ));
+    }
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let requests = match verification_repo.queue(status, limit, offset).await {
+        Ok(requests) => requests,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las solicitudes"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(requests, "Solicitudes obtenidas exitosamente")))
+}
+
+pub async fn approve_verification(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    admin: RequirePermission<perm::VerifyAccounts>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReviewVerificationRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let reason = payload.reason.as_deref().map(str::trim).filter(|reason| !reason.is_empty());
+    let request = match verification_repo.approve(id, admin.user.id, reason).await {
+        Ok(Some(request)) => request,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Solicitud no encontrada o ya revisada"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al aprobar la solicitud"))
+        ))
+    };
+
+    tracing::info!("{} verificó la cuenta {} ({})", admin.user.username, request.user_id, request.badge_type);
+    notify_verification_review(&user_repo, mailer, &request).await;
+    Ok(Json(ApiResponse::success(request, "Solicitud aprobada. La cuenta ya está verificada")))
+}
+
+pub async fn reject_verification(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    admin: RequirePermission<perm::VerifyAccounts>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReviewVerificationRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    // El usuario recibe el motivo del rechazo
+    let Some(reason) = payload.reason.as_deref().map(str::trim).filter(|reason| !reason.is_empty()) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Indica el motivo del rechazo"))
+        ));
+    };
+
+    let request = match verification_repo.reject(id, admin.user.id, reason).await {
+        Ok(Some(request)) => request,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Solicitud no encontrada o ya revisada"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al rechazar la solicitud"))
+        ))
+    };
+
+    tracing::info!("{} rechazó la verificación de {}", admin.user.username, request.user_id);
+    notify_verification_review(&user_repo, mailer, &request).await;
+    Ok(Json(ApiResponse::success(request, "Solicitud rechazada")))
+}
+
+pub async fn revoke_verification(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    admin: RequirePermission<perm::VerifyAccounts>,
+    Path(username): Path<String>,
+    Json(payload): Json<RevokeVerificationRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = find_user(&user_repo, &username).await?;
+
+    match verification_repo.revoke(user.id, admin.user.id, payload.reason.trim()).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("La cuenta no está verificada"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al retirar la verificación"))
+        ))
+    }
+
+    tracing::info!("{} retiró la verificación de {}", admin.user.username, user.username);
+    Ok(Json(ApiResponse::success((), "Verificación retirada")))
+}
+
+pub async fn verification_history(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    _admin: RequirePermission<perm::VerifyAccounts>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = find_user(&user_repo, &username).await?;
+
+    let history = match verification_repo.history(user.id).await {
+        Ok(history) => history,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener el historial"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(history, "Historial obtenido exitosamente")))
+}
+
+// Avisa por email del resultado de la revisión; un fallo no afecta a la decisión
+async fn notify_verification_review(
+    user_repo: &UserRepository,
+    mailer: Arc<dyn Mailer>,
+    request: &VerificationRequest,
+) {
+    let user = match user_repo.find_by_id(request.user_id).await {
+        Ok(Some(user)) => user,
+        _ => return,
+    };
+
+    let body = if request.status == "approved" {
+        format!("Hola {},\n\nHemos aprobado tu solicitud: tu cuenta ya muestra la insignia de verificación.\n", user.username)
+    } else {
+        format!(
+            "Hola {},\n\nHemos revisado tu solicitud de verificación y no podemos aprobarla.\n\nMotivo: {}\n",
+            user.username,
+            request.review_reason.as_deref().unwrap_or("-")
+        )
+    };
+
+    let message = EmailMessage {
+        to: user.email,
+        subject: "Tu solicitud de verificación en Pitaia".to_string(),
+        body,
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Error enviando aviso de verificación: {}", e);
+        }
+    });
+}
+
+// Sólo los roles adicionales se asignan; "user" lo tienen todas las cuentas
+fn parse_assignable_role(value: &str) -> Result<Role, (StatusCode, Json<ApiResponse<()>>)> {
+    match Role::parse(value) {
+        Some(Role::User) | None => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Rol inválido. Valores permitidos: moderator, admin"))
+        )),
+        Some(role) => Ok(role),
+    }
+}
+
+async fn find_user(
+    user_repo: &UserRepository,
+    username: &str,
+) -> Result<User, (StatusCode, Json<ApiResponse<()>>)> {
+    match user_repo.find_by_username(username).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
@@ -1,17 +1,39 @@
 use axum::{
     extract::{Json, State},
-    http::StatusCode,
+    http::{header, StatusCode},
     response::IntoResponse,
 };
 use validator::Validate;
 use std::sync::Arc;
+use chrono::{Duration, Utc};
 
-use crate::models::{ApiResponse, AuthResponse, CreateUser, LoginUser, UserProfile};
-use crate::auth::{JwtService, verify_password};
-use crate::repository::UserRepository;
+use crate::models::{
+    is_reserved_username, ApiResponse, AuthResponse, CreateUser, LoginResponse, LoginUser, RefreshToken,
+    RefreshTokenRequest, TwoFactorPending, User,
+};
+use crate::auth::{
+    AccountStateCache, JwtService, generate_opaque_token, hash_password, hash_token, password_needs_rehash,
+    verify_password,
+};
+use crate::mailer::Mailer;
+use crate::repository::{
+    EmailVerificationRepository, RefreshTokenRepository, RoleRepository, SessionRepository,
+    TwoFactorRepository, UserRepository,
+};
+use crate::middleware::{AuthUser, ClientInfo};
+use crate::throttle::{LoginBlock, LoginGuard};
+use super::account::username_grace_period;
+use super::verification::send_verification_email;
 
 pub async fn register(
     State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(email_verification_repo): State<Arc<EmailVerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    client: ClientInfo,
     Json(payload): Json<CreateUser>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
     if let Err(validation_errors) = payload.validate() {
@@ -21,12 +43,25 @@ pub async fn register(
         ));
     }
 
-    // Verificar si el usuario ya existe
-    if let Ok(Some(_)) = user_repo.find_by_username(&payload.username).await {
+    if is_reserved_username(&payload.username) {
         return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Ese nombre de usuario está reservado"))
+        ));
+    }
+
+    // Sin distinguir mayúsculas y respetando los nombres retenidos tras un cambio
+    let since = Utc::now() - username_grace_period();
+    match user_repo.is_username_available(&payload.username, None, since).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
             StatusCode::CONFLICT,
             Json(ApiResponse::error("El nombre de usuario ya está en uso"))
-        ));
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
     }
 
     if let Ok(Some(_)) = user_repo.find_by_email(&payload.email).await {
@@ -45,20 +80,13 @@ pub async fn register(
         ))
     };
 
-    // Generar token
-    let jwt_service = JwtService::new();
-    let token = match jwt_service.generate_token(user.id, &user.username) {
-        Ok(token) => token,
-        Err(_) => return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error("Error generando token"))
-        ))
-    };
+    // Enviar correo de verificación; un fallo aquí no impide el registro
+    if let Err(e) = send_verification_email(&email_verification_repo, mailer, &user).await {
+        tracing::error!("Error generando verificación de email: {}", e);
+    }
 
-    let auth_response = AuthResponse {
-        token,
-        user: user.into(),
-    };
+    // Abrir sesión y generar tokens
+    let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
 
     Ok((
         StatusCode::CREATED,
@@ -68,14 +96,105 @@ pub async fn register(
 
 pub async fn login(
     State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    State(login_guard): State<Arc<LoginGuard>>,
+    client: ClientInfo,
     Json(payload): Json<LoginUser>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
-    // Buscar usuario
-    let user = match user_repo.find_by_username(&payload.username).await {
-        Ok(Some(user)) => user,
+    let ip_address = client.ip_address.as_deref();
+
+    // Rechazar intentos mientras la cuenta o la IP estén bloqueadas
+    match login_guard.check(&payload.username, ip_address).await {
+        Ok(Some(block)) => return Err(login_block_response(block)),
+        Ok(None) => {},
+        Err(e) => tracing::error!("Error consultando intentos de login: {}", e),
+    }
+
+    // Buscar usuario (incluye cuentas pendientes de eliminación, que se recuperan al entrar)
+    let user = match user_repo.find_for_login(&payload.username).await {
+        Ok(user) => user,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // Verificar contraseña (los usuarios inexistentes también cuentan como intento fallido)
+    let password_ok = match user.as_ref() {
+        Some(user) => verify_password(&payload.password, &user.password_hash).await.unwrap_or(false),
+        None => false,
+    };
+
+    let user = match user {
+        Some(user) if password_ok => user,
+        user => {
+            let user_id = user.map(|user| user.id);
+            return match login_guard.record_failure(&payload.username, ip_address, user_id).await {
+                Ok(Some(block @ LoginBlock::AccountLocked { .. })) => Err(login_block_response(block)),
+                Ok(_) => Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error("Credenciales inválidas"))
+                )),
+                Err(e) => {
+                    tracing::error!("Error registrando intento de login fallido: {}", e);
+                    Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiResponse::error("Credenciales inválidas"))
+                    ))
+                }
+            };
+        }
+    };
+
+    // Migrar hashes antiguos (bcrypt o parámetros desactualizados) al algoritmo actual
+    if password_needs_rehash(&user.password_hash) {
+        match hash_password(&payload.password).await {
+            Ok(new_hash) => {
+                if let Err(e) = user_repo.update_password_hash(user.id, &new_hash).await {
+                    tracing::warn!("No se pudo re-hashear la contraseña de {}: {}", user.id, e);
+                }
+            },
+            Err(e) => tracing::warn!("No se pudo re-hashear la contraseña de {}: {}", user.id, e),
+        }
+    }
+
+    // Con 2FA activo sólo se emite un reto; los tokens se entregan tras validar el código
+    // (y sólo entonces se reinician los intentos fallidos)
+    if let Some(pending) = two_factor_challenge(&two_factor_repo, &user).await? {
+        return Ok(Json(ApiResponse::success(
+            LoginResponse::TwoFactorRequired(pending),
+            "Se requiere el código de verificación en dos pasos"
+        )));
+    }
+
+    // Abrir sesión y generar tokens
+    let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
+
+    if let Err(e) = login_guard.record_success(&payload.username).await {
+        tracing::error!("Error reiniciando intentos de login: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(LoginResponse::Authenticated(auth_response), "Login exitoso")))
+}
+
+pub async fn refresh(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let stored = match refresh_token_repo.find_by_hash(&hash_token(&payload.refresh_token)).await {
+        Ok(Some(token)) => token,
         Ok(None) => return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::error("Credenciales inválidas"))
+            Json(ApiResponse::error("Refresh token inválido"))
         )),
         Err(_) => return Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -83,17 +202,55 @@ pub async fn login(
         ))
     };
 
-    // Verificar contraseña
-    if !verify_password(&payload.password, &user.password_hash).unwrap_or(false) {
+    // Un token ya usado o revocado indica robo: se revoca toda la sesión
+    if stored.revoked_at.is_some() {
+        return Err(revoke_reused_session(&session_repo, &account_cache, &stored).await);
+    }
+
+    if stored.expires_at <= Utc::now() {
         return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::error("Credenciales inválidas"))
+            Json(ApiResponse::error("Refresh token expirado"))
         ));
     }
 
-    // Generar token
-    let jwt_service = JwtService::new();
-    let token = match jwt_service.generate_token(user.id, &user.username) {
+    let user = match user_repo.find_by_id(stored.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Refresh token inválido"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if user.is_banned() {
+        return Err(banned_response(&user));
+    }
+
+    let roles = match role_repo.roles_for_user(user.id).await {
+        Ok(roles) => roles,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let refresh_token = generate_opaque_token();
+    let expires_at = Utc::now() + jwt_service.refresh_ttl();
+
+    match refresh_token_repo.rotate(&stored, &hash_token(&refresh_token), expires_at).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return Err(revoke_reused_session(&session_repo, &account_cache, &stored).await),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    let token = match jwt_service.generate_token(&user, stored.session_id, &roles) {
         Ok(token) => token,
         Err(_) => return Err((
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -103,8 +260,238 @@ pub async fn login(
 
     let auth_response = AuthResponse {
         token,
+        refresh_token,
+        expires_in: jwt_service.access_ttl().num_seconds(),
+        user: user.into(),
+    };
+
+    Ok(Json(ApiResponse::success(auth_response, "Token renovado")))
+}
+
+pub async fn logout(
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let Some(session_id) = auth_user.session_id else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("No hay una sesión que cerrar"))
+        ));
+    };
+
+    if session_repo.revoke(session_id, auth_user.id).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al cerrar sesión"))
+        ));
+    }
+    account_cache.invalidate_user(auth_user.id);
+
+    Ok(Json(ApiResponse::success((), "Sesión cerrada")))
+}
+
+pub async fn logout_all(
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if session_repo.revoke_all_for_user(auth_user.id).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al cerrar sesiones"))
+        ));
+    }
+    account_cache.invalidate_user(auth_user.id);
+
+    Ok(Json(ApiResponse::success((), "Todas las sesiones fueron cerradas")))
+}
+
+// Claves públicas vigentes para que otros servicios validen los access tokens
+pub async fn jwks(State(jwt_service): State<Arc<JwtService>>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(jwt_service.jwks()),
+    )
+}
+
+// Registra una sesión nueva para el dispositivo y emite su primer par de tokens
+pub(crate) async fn start_session(
+    user_repo: &UserRepository,
+    jwt_service: &JwtService,
+    session_repo: &SessionRepository,
+    refresh_token_repo: &RefreshTokenRepository,
+    role_repo: &RoleRepository,
+    mut user: User,
+    client: &ClientInfo,
+) -> Result<AuthResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if user.is_banned() {
+        return Err(banned_response(&user));
+    }
+
+    // Sólo con la autenticación completa (incluido el segundo factor) se recupera la cuenta
+    restore_pending_deletion(user_repo, &mut user).await?;
+
+    let expires_at = Utc::now() + jwt_service.refresh_ttl();
+
+    let session = match session_repo.create(
+        user.id,
+        client.user_agent.as_deref(),
+        client.ip_address.as_deref(),
+        expires_at,
+    ).await {
+        Ok(session) => session,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al crear la sesión"))
+        ))
+    };
+
+    let roles = match role_repo.roles_for_user(user.id).await {
+        Ok(roles) => roles,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let token = match jwt_service.generate_token(&user, session.id, &roles) {
+        Ok(token) => token,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error generando token"))
+        ))
+    };
+
+    let refresh_token = generate_opaque_token();
+    if refresh_token_repo.create(user.id, session.id, &hash_token(&refresh_token), expires_at).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error generando token"))
+        ));
+    }
+
+    Ok(AuthResponse {
+        token,
+        refresh_token,
+        expires_in: jwt_service.access_ttl().num_seconds(),
         user: user.into(),
+    })
+}
+
+// Si la cuenta tiene 2FA activo, crea un reto de login en lugar de abrir sesión
+pub(crate) async fn two_factor_challenge(
+    two_factor_repo: &TwoFactorRepository,
+    user: &User,
+) -> Result<Option<TwoFactorPending>, (StatusCode, Json<ApiResponse<()>>)> {
+    let two_factor_enabled = match two_factor_repo.is_enabled(user.id).await {
+        Ok(enabled) => enabled,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
     };
 
-    Ok(Json(ApiResponse::success(auth_response, "Login exitoso")))
+    if !two_factor_enabled {
+        return Ok(None);
+    }
+
+    let challenge_token = generate_opaque_token();
+    let ttl = Duration::minutes(5);
+
+    if two_factor_repo.create_challenge(user.id, &hash_token(&challenge_token), Utc::now() + ttl).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    Ok(Some(TwoFactorPending {
+        two_factor_required: true,
+        challenge_token,
+        expires_in: ttl.num_seconds(),
+    }))
+}
+
+async fn revoke_reused_session(
+    session_repo: &SessionRepository,
+    account_cache: &AccountStateCache,
+    stored: &RefreshToken,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    tracing::warn!("Reutilización de refresh token detectada; revocando sesión {}", stored.session_id);
+
+    if session_repo.revoke(stored.session_id, stored.user_id).await.is_err() {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        );
+    }
+    account_cache.invalidate_user(stored.user_id);
+
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiResponse::error("Refresh token reutilizado; sesión revocada"))
+    )
+}
+
+fn banned_response(user: &User) -> (StatusCode, Json<ApiResponse<()>>) {
+    let message = match user.banned_until {
+        Some(until) => format!("Cuenta suspendida hasta {}", until.format("%d/%m/%Y %H:%M UTC")),
+        None => "Cuenta suspendida".to_string(),
+    };
+
+    (StatusCode::FORBIDDEN, Json(ApiResponse::error(&message)))
+}
+
+pub(crate) fn login_block_response(block: LoginBlock) -> (StatusCode, Json<ApiResponse<()>>) {
+    let now = Utc::now();
+
+    match block {
+        LoginBlock::AccountLocked { until } => (
+            StatusCode::LOCKED,
+            Json(ApiResponse::error(&format!(
+                "Cuenta bloqueada temporalmente por demasiados intentos fallidos. Inténtalo de nuevo en {} minutos",
+                ((until - now).num_seconds() + 59) / 60
+            )))
+        ),
+        LoginBlock::IpBlocked { until } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::error(&format!(
+                "Demasiados intentos desde esta dirección. Inténtalo de nuevo en {} minutos",
+                ((until - now).num_seconds() + 59) / 60
+            )))
+        ),
+        LoginBlock::Backoff { until } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::error(&format!(
+                "Demasiados intentos fallidos. Espera {} segundos",
+                (until - now).num_seconds().max(1)
+            )))
+        ),
+    }
+}
+
+/// Iniciar sesión (con cualquier método) durante el periodo de gracia cancela la eliminación
+/// de la cuenta.
+async fn restore_pending_deletion(
+    user_repo: &UserRepository,
+    user: &mut User,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    if user.deletion_scheduled_at.is_none() {
+        return Ok(());
+    }
+
+    match user_repo.cancel_deletion(user.id).await {
+        Ok(_) => {
+            tracing::info!("Eliminación de la cuenta {} cancelada al iniciar sesión", user.id);
+            user.is_active = true;
+            user.deletion_requested_at = None;
+            user.deletion_scheduled_at = None;
+            Ok(())
+        },
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
 }
@@ -0,0 +1,186 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::models::{ApiResponse, MuteRequest, User};
+use crate::repository::{BlockRepository, SuggestionRepository, UserRepository};
+use crate::middleware::AuthUser;
+use super::follows::FollowListQuery;
+use super::suggestions::refresh_suggestions;
+
+pub async fn block_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_other_user(&user_repo, &auth_user, &username).await?;
+
+    // También deja de seguirse en ambos sentidos y se descartan las solicitudes pendientes
+    match block_repo.block(auth_user.id, target.id).await {
+        Ok(true) => {
+            refresh_suggestions(&suggestion_repo, auth_user.id).await;
+            refresh_suggestions(&suggestion_repo, target.id).await;
+            Ok(Json(ApiResponse::success((), "Usuario bloqueado")))
+        },
+        Ok(false) => Ok(Json(ApiResponse::success((), "Ya habías bloqueado a este usuario"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al bloquear al usuario"))
+        ))
+    }
+}
+
+pub async fn unblock_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_other_user(&user_repo, &auth_user, &username).await?;
+
+    match block_repo.unblock(auth_user.id, target.id).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Usuario desbloqueado"))),
+        Ok(false) => Ok(Json(ApiResponse::success((), "No tenías bloqueado a este usuario"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al desbloquear al usuario"))
+        ))
+    }
+}
+
+pub async fn mute_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+    Json(payload): Json<MuteRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let target = find_other_user(&user_repo, &auth_user, &username).await?;
+    let expires_at = payload.duration_hours.map(|hours| Utc::now() + Duration::hours(hours));
+
+    if block_repo.mute(auth_user.id, target.id, expires_at).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al silenciar al usuario"))
+        ));
+    }
+
+    let message = match expires_at {
+        Some(expires_at) => format!("Usuario silenciado hasta el {}", expires_at.format("%d/%m/%Y %H:%M UTC")),
+        None => "Usuario silenciado".to_string(),
+    };
+
+    Ok(Json(ApiResponse::success((), &message)))
+}
+
+pub async fn unmute_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_other_user(&user_repo, &auth_user, &username).await?;
+
+    match block_repo.unmute(auth_user.id, target.id).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Usuario ya no silenciado"))),
+        Ok(false) => Ok(Json(ApiResponse::success((), "No tenías silenciado a este usuario"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al quitar el silencio"))
+        ))
+    }
+}
+
+pub async fn list_blocked(
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match block_repo.blocked_users(auth_user.id, params.limit(), params.offset()).await {
+        Ok(blocked) => Ok(Json(ApiResponse::success(blocked, "Usuarios bloqueados obtenidos exitosamente"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los usuarios bloqueados"))
+        ))
+    }
+}
+
+pub async fn list_muted(
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match block_repo.muted_users(auth_user.id, params.limit(), params.offset()).await {
+        Ok(muted) => Ok(Json(ApiResponse::success(muted, "Usuarios silenciados obtenidos exitosamente"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los usuarios silenciados"))
+        ))
+    }
+}
+
+/// Responde 404 si hay un bloqueo entre quien consulta y `user_id`: para ambos la otra
+/// cuenta deja de existir.
+pub(crate) async fn ensure_not_blocked(
+    block_repo: &BlockRepository,
+    viewer_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let Some(viewer_id) = viewer_id.filter(|viewer_id| *viewer_id != user_id) else {
+        return Ok(());
+    };
+
+    match block_repo.is_blocked_between(viewer_id, user_id).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+async fn find_other_user(
+    user_repo: &UserRepository,
+    auth_user: &AuthUser,
+    username: &str,
+) -> Result<User, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = match user_repo.find_by_username(username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if user.id == auth_user.id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("No puedes hacer esto con tu propia cuenta"))
+        ));
+    }
+
+    Ok(user)
+}
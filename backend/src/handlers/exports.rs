@@ -0,0 +1,139 @@
+use axum::{
+    body::Body,
+    extract::{Json, Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use tokio_util::io::ReaderStream;
+
+use crate::models::{ApiResponse, DataExportInfo};
+use crate::auth::hash_token;
+use crate::repository::ExportRepository;
+use crate::middleware::AuthUser;
+
+pub async fn request_export(
+    State(export_repo): State<Arc<ExportRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let latest = match export_repo.latest_for_user(auth_user.id).await {
+        Ok(latest) => latest,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if let Some(latest) = latest {
+        if latest.status == "pending" || latest.status == "processing" {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::error("Ya hay una exportación en curso"))
+            ));
+        }
+
+        let available_at = latest.requested_at + Duration::hours(export_cooldown_hours());
+        if latest.status != "failed" && available_at > Utc::now() {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::error(&format!(
+                    "Podrás solicitar otra exportación a partir del {}",
+                    available_at.format("%d/%m/%Y %H:%M UTC")
+                )))
+            ));
+        }
+    }
+
+    let export = match export_repo.create(auth_user.id).await {
+        Ok(export) => export,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al solicitar la exportación"))
+        ))
+    };
+
+    let info: DataExportInfo = export.into();
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ApiResponse::success(info, "Exportación en preparación. Te avisaremos por email cuando esté lista"))
+    ))
+}
+
+pub async fn export_status(
+    State(export_repo): State<Arc<ExportRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match export_repo.latest_for_user(auth_user.id).await {
+        Ok(Some(export)) => {
+            let info: DataExportInfo = export.into();
+            Ok(Json(ApiResponse::success(info, "Estado de la exportación")))
+        },
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("No has solicitado ninguna exportación"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+// El enlace del correo basta para descargar; caduca a los pocos días
+pub async fn download_export(
+    State(export_repo): State<Arc<ExportRepository>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let export = match export_repo.find_downloadable(&hash_token(&token)).await {
+        Ok(Some(export)) => export,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("El enlace de descarga es inválido o ha caducado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let Some(file_path) = export.file_path else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("El enlace de descarga es inválido o ha caducado"))
+        ));
+    };
+
+    // El paquete puede pesar mucho: se envía por trozos en lugar de cargarlo en memoria
+    let file = match tokio::fs::File::open(&file_path).await {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::error!("No se pudo leer la exportación {}: {}", export.id, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error al leer la exportación"))
+            ));
+        }
+    };
+
+    let filename = format!(
+        "attachment; filename=\"pitaia-datos-{}.zip\"",
+        export.requested_at.format("%Y%m%d")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, filename),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    ))
+}
+
+fn export_cooldown_hours() -> i64 {
+    std::env::var("EXPORT_COOLDOWN_HOURS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(24)
+}
@@ -0,0 +1,312 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{ApiResponse, User, UserProfile};
+use crate::repository::{BlockRepository, FollowRepository, SuggestionRepository, UserRepository};
+use crate::middleware::AuthUser;
+use super::blocks::ensure_not_blocked;
+use super::suggestions::{refresh_follower_suggestions, refresh_suggestions};
+
+#[derive(Deserialize)]
+pub struct FollowListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl FollowListQuery {
+    pub(crate) fn limit(&self) -> i64 {
+        self.limit.unwrap_or(20).clamp(1, 100)
+    }
+
+    pub(crate) fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+pub async fn follow_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_target(&user_repo, &username).await?;
+    ensure_not_blocked(&block_repo, Some(auth_user.id), target.id).await?;
+
+    if target.id == auth_user.id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("No puedes seguirte a ti mismo"))
+        ));
+    }
+
+    // Las cuentas privadas reciben una solicitud; el resto, el seguimiento directo
+    let result = match follow_repo.can_view(Some(auth_user.id), &target).await {
+        Ok(true) => follow_repo.follow(auth_user.id, target.id).await.map(|created| {
+            (if created { "Ahora sigues a este usuario" } else { "Ya seguías a este usuario" }, created)
+        }),
+        Ok(false) => follow_repo.request_follow(auth_user.id, target.id).await.map(|created| {
+            (if created { "Solicitud de seguimiento enviada" } else { "Ya habías enviado una solicitud" }, false)
+        }),
+        Err(e) => Err(e),
+    };
+
+    let (message, followed) = match result {
+        Ok(result) => result,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al seguir al usuario"))
+        ))
+    };
+    refresh_suggestions(&suggestion_repo, auth_user.id).await;
+    if followed {
+        refresh_follower_suggestions(&suggestion_repo, auth_user.id).await;
+    }
+
+    let profile = relationship_profile(&user_repo, &follow_repo, auth_user.id, target.id).await?;
+    Ok(Json(ApiResponse::success(profile, message)))
+}
+
+pub async fn unfollow_user(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_target(&user_repo, &username).await?;
+
+    let removed = match follow_repo.unfollow(auth_user.id, target.id).await {
+        Ok(removed) => removed,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al dejar de seguir al usuario"))
+        ))
+    };
+
+    // Sin seguimiento, puede que hubiera una solicitud pendiente que retirar
+    let cancelled = !removed && match follow_repo.delete_request(auth_user.id, target.id).await {
+        Ok(cancelled) => cancelled,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al retirar la solicitud"))
+        ))
+    };
+    refresh_suggestions(&suggestion_repo, auth_user.id).await;
+
+    let profile = relationship_profile(&user_repo, &follow_repo, auth_user.id, target.id).await?;
+    let message = if removed {
+        "Has dejado de seguir a este usuario"
+    } else if cancelled {
+        "Solicitud de seguimiento retirada"
+    } else {
+        "No seguías a este usuario"
+    };
+    Ok(Json(ApiResponse::success(profile, message)))
+}
+
+pub async fn list_followers(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: Option<AuthUser>,
+    Path(username): Path<String>,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_target(&user_repo, &username).await?;
+    let viewer_id = auth_user.map(|u| u.id);
+    ensure_not_blocked(&block_repo, viewer_id, target.id).await?;
+    check_can_view(&follow_repo, viewer_id, &target).await?;
+
+    let followers = match follow_repo.followers(target.id, viewer_id, params.limit(), params.offset()).await {
+        Ok(followers) => followers,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los seguidores"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(followers, "Seguidores obtenidos exitosamente")))
+}
+
+pub async fn list_following(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: Option<AuthUser>,
+    Path(username): Path<String>,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_target(&user_repo, &username).await?;
+    let viewer_id = auth_user.map(|u| u.id);
+    ensure_not_blocked(&block_repo, viewer_id, target.id).await?;
+    check_can_view(&follow_repo, viewer_id, &target).await?;
+
+    let following = match follow_repo.following(target.id, viewer_id, params.limit(), params.offset()).await {
+        Ok(following) => following,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los seguidos"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(following, "Seguidos obtenidos exitosamente")))
+}
+
+/// Seguidores del usuario a los que también sigue quien consulta.
+pub async fn list_mutual_followers(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = find_target(&user_repo, &username).await?;
+    ensure_not_blocked(&block_repo, Some(auth_user.id), target.id).await?;
+    check_can_view(&follow_repo, Some(auth_user.id), &target).await?;
+
+    let mutuals = match follow_repo.mutual_followers(auth_user.id, target.id, params.limit(), params.offset()).await {
+        Ok(mutuals) => mutuals,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los seguidores en común"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(mutuals, "Seguidores en común obtenidos exitosamente")))
+}
+
+pub async fn list_follow_requests(
+    State(follow_repo): State<Arc<FollowRepository>>,
+    auth_user: AuthUser,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let requests = match follow_repo.pending_requests(auth_user.id, params.limit(), params.offset()).await {
+        Ok(requests) => requests,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las solicitudes"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(requests, "Solicitudes obtenidas exitosamente")))
+}
+
+pub async fn approve_follow_request(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let requester = find_target(&user_repo, &username).await?;
+
+    match follow_repo.approve_request(requester.id, auth_user.id).await {
+        Ok(true) => {
+            // Quien pidió seguir estrena seguimiento
+            refresh_suggestions(&suggestion_repo, requester.id).await;
+            refresh_follower_suggestions(&suggestion_repo, requester.id).await;
+            Ok(Json(ApiResponse::success((), "Solicitud aprobada")))
+        },
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Solicitud no encontrada"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al aprobar la solicitud"))
+        ))
+    }
+}
+
+pub async fn reject_follow_request(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let requester = find_target(&user_repo, &username).await?;
+
+    match follow_repo.delete_request(requester.id, auth_user.id).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Solicitud rechazada"))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Solicitud no encontrada"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al rechazar la solicitud"))
+        ))
+    }
+}
+
+pub(crate) async fn check_can_view(
+    follow_repo: &FollowRepository,
+    viewer_id: Option<Uuid>,
+    owner: &User,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    match follow_repo.can_view(viewer_id, owner).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Esta cuenta es privada"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+async fn find_target(
+    user_repo: &UserRepository,
+    username: &str,
+) -> Result<User, (StatusCode, Json<ApiResponse<()>>)> {
+    match user_repo.find_by_username(username).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+// Perfil recargado tras el cambio, con los contadores y la relación ya actualizados
+async fn relationship_profile(
+    user_repo: &UserRepository,
+    follow_repo: &FollowRepository,
+    viewer_id: Uuid,
+    user_id: Uuid,
+) -> Result<UserProfile, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = match user_repo.find_by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    match follow_repo.relationship(viewer_id, user_id).await {
+        Ok(relationship) => Ok(UserProfile::from(user).with_relationship(relationship)),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
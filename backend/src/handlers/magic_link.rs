@@ -0,0 +1,171 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{
+    ApiResponse, LoginResponse, MagicLinkLoginRequest, MagicLinkPending, MagicLinkRequest,
+};
+use crate::auth::{generate_opaque_token, hash_token, JwtService};
+use crate::mailer::{EmailMessage, Mailer};
+use crate::repository::{
+    MagicLinkRepository, RefreshTokenRepository, RoleRepository, SessionRepository, TwoFactorRepository,
+    UserRepository,
+};
+use crate::middleware::ClientInfo;
+use super::app_url;
+use super::auth::{start_session, two_factor_challenge};
+
+pub async fn request_link(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(magic_link_repo): State<Arc<MagicLinkRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    client: ClientInfo,
+    Json(payload): Json<MagicLinkRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let email = payload.email.trim().to_lowercase();
+
+    // Límite por dirección; cuenta también los emails sin cuenta para no revelar cuáles existen
+    match magic_link_repo.count_since(&email, Utc::now() - Duration::hours(1)).await {
+        Ok(count) if count >= max_links_per_hour() => return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::error("Has pedido demasiados enlaces. Inténtalo de nuevo más tarde"))
+        )),
+        Ok(_) => {},
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    let user = match user_repo.find_by_email_for_login(&email).await {
+        Ok(user) => user,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let token = generate_opaque_token();
+    let nonce = generate_opaque_token();
+    let ttl = Duration::minutes(link_ttl_minutes());
+
+    if magic_link_repo.create(
+        user.as_ref().map(|user| user.id),
+        &email,
+        &hash_token(&token),
+        &hash_token(&nonce),
+        client.ip_address.as_deref(),
+        Utc::now() + ttl,
+    ).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    if let Some(user) = user {
+        let message = EmailMessage {
+            to: user.email,
+            subject: "Tu enlace para entrar en Pitaia".to_string(),
+            body: format!(
+                "Hola {},\n\nAbre este enlace en el mismo dispositivo donde lo pediste para iniciar sesión (válido por {} minutos):\n{}/magic-link?token={}\n\nSi no lo solicitaste, ignora este correo.",
+                user.username,
+                link_ttl_minutes(),
+                app_url(),
+                token
+            ),
+        };
+
+        // Enviar en segundo plano para que el tiempo de respuesta no delate la existencia de la cuenta
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(message).await {
+                tracing::error!("Error enviando enlace de acceso: {}", e);
+            }
+        });
+    }
+
+    let pending = MagicLinkPending {
+        nonce,
+        expires_in: ttl.num_seconds(),
+    };
+
+    Ok(Json(ApiResponse::success(
+        pending,
+        "Si el email está registrado, recibirás un enlace para iniciar sesión"
+    )))
+}
+
+pub async fn login_with_link(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(magic_link_repo): State<Arc<MagicLinkRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    client: ClientInfo,
+    Json(payload): Json<MagicLinkLoginRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    // Sin el nonce del dispositivo solicitante el enlace no sirve, aunque se intercepte el correo
+    let user_id = match magic_link_repo.consume(&hash_token(&payload.token), &hash_token(&payload.nonce)).await {
+        Ok(Some(user_id)) => user_id,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Enlace inválido o expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let user = match user_repo.find_by_id_for_login(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Enlace inválido o expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // El enlace sustituye a la contraseña, no al segundo factor
+    if let Some(pending) = two_factor_challenge(&two_factor_repo, &user).await? {
+        return Ok(Json(ApiResponse::success(
+            LoginResponse::TwoFactorRequired(pending),
+            "Se requiere el código de verificación en dos pasos"
+        )));
+    }
+
+    let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
+
+    Ok(Json(ApiResponse::success(LoginResponse::Authenticated(auth_response), "Login exitoso")))
+}
+
+fn link_ttl_minutes() -> i64 {
+    std::env::var("MAGIC_LINK_TTL_MINUTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(10)
+}
+
+fn max_links_per_hour() -> i64 {
+    std::env::var("MAGIC_LINK_MAX_PER_HOUR")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(5)
+}
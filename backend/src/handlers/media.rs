@@ -0,0 +1,170 @@
+use axum::{
+    extract::{Json, Multipart, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::models::{ApiResponse, UploadedImage};
+use crate::media::{self, ProfileImage};
+use crate::repository::UserRepository;
+use crate::storage::Storage;
+use crate::middleware::AuthUser;
+
+pub async fn upload_avatar(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(storage): State<Arc<dyn Storage>>,
+    auth_user: AuthUser,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let uploaded = upload_image(&user_repo, storage.as_ref(), &auth_user, multipart, ProfileImage::Avatar).await?;
+    Ok(Json(ApiResponse::success(uploaded, "Avatar actualizado")))
+}
+
+pub async fn delete_avatar(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(storage): State<Arc<dyn Storage>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    remove_image(&user_repo, storage.as_ref(), &auth_user, ProfileImage::Avatar).await?;
+    Ok(Json(ApiResponse::success((), "Avatar eliminado")))
+}
+
+pub async fn upload_banner(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(storage): State<Arc<dyn Storage>>,
+    auth_user: AuthUser,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let uploaded = upload_image(&user_repo, storage.as_ref(), &auth_user, multipart, ProfileImage::Banner).await?;
+    Ok(Json(ApiResponse::success(uploaded, "Banner actualizado")))
+}
+
+pub async fn delete_banner(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(storage): State<Arc<dyn Storage>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    remove_image(&user_repo, storage.as_ref(), &auth_user, ProfileImage::Banner).await?;
+    Ok(Json(ApiResponse::success((), "Banner eliminado")))
+}
+
+async fn upload_image(
+    user_repo: &UserRepository,
+    storage: &dyn Storage,
+    auth_user: &AuthUser,
+    mut multipart: Multipart,
+    kind: ProfileImage,
+) -> Result<UploadedImage, (StatusCode, Json<ApiResponse<()>>)> {
+    // La imagen llega en el campo "image"; el resto de campos se ignora
+    let mut data = None;
+    loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some("image") => match field.bytes().await {
+                Ok(bytes) => {
+                    data = Some(bytes);
+                    break;
+                },
+                Err(e) => return Err((
+                    e.status(),
+                    Json(ApiResponse::error("No se pudo leer la imagen"))
+                ))
+            },
+            Ok(Some(_)) => continue,
+            Ok(None) => break,
+            Err(e) => return Err((
+                e.status(),
+                Json(ApiResponse::error("Formulario inválido"))
+            ))
+        }
+    }
+
+    let Some(data) = data else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Falta el campo \"image\""))
+        ));
+    };
+
+    let Some(format) = media::sniff_format(&data) else {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ApiResponse::error("Formato no soportado. Usa JPEG, PNG, GIF o WebP"))
+        ));
+    };
+
+    // Decodificar y redimensionar es costoso; se hace fuera del runtime
+    let variants = match tokio::task::spawn_blocking(move || media::process(kind, &data, format)).await {
+        Ok(Ok(variants)) => variants,
+        Ok(Err(e)) => {
+            tracing::warn!("Imagen rechazada: {}", e);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("No se pudo procesar la imagen"))
+            ));
+        },
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let stored = match media::store(storage, kind, auth_user.id, variants).await {
+        Ok(stored) => stored,
+        Err(e) => {
+            tracing::error!("Error guardando imagen: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error al guardar la imagen"))
+            ));
+        }
+    };
+
+    let result = match kind {
+        ProfileImage::Avatar => user_repo.set_avatar(auth_user.id, Some(&stored.url), Some(&stored.key)).await,
+        ProfileImage::Banner => user_repo.set_banner(auth_user.id, Some(&stored.url), Some(&stored.key)).await,
+    };
+
+    match result {
+        Ok(Some(old_key)) => media::remove(storage, kind, &old_key).await,
+        Ok(None) => {},
+        Err(_) => {
+            media::remove(storage, kind, &stored.key).await;
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error al actualizar el perfil"))
+            ));
+        }
+    }
+
+    Ok(UploadedImage {
+        url: stored.url,
+        variants: stored.variants
+            .into_iter()
+            .map(|(name, url)| (name.to_string(), url))
+            .collect(),
+    })
+}
+
+async fn remove_image(
+    user_repo: &UserRepository,
+    storage: &dyn Storage,
+    auth_user: &AuthUser,
+    kind: ProfileImage,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let result = match kind {
+        ProfileImage::Avatar => user_repo.set_avatar(auth_user.id, None, None).await,
+        ProfileImage::Banner => user_repo.set_banner(auth_user.id, None, None).await,
+    };
+
+    match result {
+        Ok(Some(old_key)) => media::remove(storage, kind, &old_key).await,
+        Ok(None) => {},
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    Ok(())
+}
@@ -1,3 +1,28 @@
+pub mod access_tokens;
+pub mod account;
+pub mod admin;
 pub mod auth;
+pub mod blocks;
+pub mod exports;
+pub mod follows;
+pub mod magic_link;
+pub mod media;
+pub mod oauth;
+pub mod password;
 pub mod posts;
+pub mod sessions;
+pub mod suggestions;
+pub mod two_factor;
 pub mod users;
+pub mod verification;
+pub mod verified_accounts;
+
+// URL pública del frontend, usada para construir enlaces en los correos
+pub(crate) fn app_url() -> String {
+    std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:3000".to_string())
+}
+
+// URL pública de la API, para enlaces que apuntan directamente a ella (descargas)
+pub(crate) fn api_url() -> String {
+    std::env::var("API_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
+}
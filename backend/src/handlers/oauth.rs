@@ -0,0 +1,380 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{
+    is_reserved_username, validate_username, ApiResponse, OAuthAuthorization, OAuthCallbackRequest,
+    OAuthCallbackResponse, OAuthSignupPending, OAuthSignupRequest,
+};
+use crate::auth::{generate_opaque_token, hash_password, hash_token, JwtService};
+use crate::mailer::Mailer;
+use crate::oauth::{generate_code_verifier, OAuthClient, OAuthProvider};
+use crate::repository::{
+    EmailVerificationRepository, IdentityRepository, RefreshTokenRepository, RoleRepository, SessionRepository,
+    TwoFactorRepository, UserRepository,
+};
+use crate::middleware::{AuthUser, ClientInfo};
+use super::auth::{start_session, two_factor_challenge};
+use super::account::username_grace_period;
+use super::verification::send_verification_email;
+
+const STATE_TTL_MINUTES: i64 = 10;
+const SIGNUP_TTL_MINUTES: i64 = 30;
+
+pub async fn list_providers(
+    State(oauth_client): State<Arc<OAuthClient>>,
+) -> impl IntoResponse {
+    Json(ApiResponse::success(oauth_client.provider_names(), "Proveedores disponibles"))
+}
+
+// Inicia la autorización; con sesión iniciada, el proveedor se vincula a la cuenta actual
+pub async fn authorize(
+    State(oauth_client): State<Arc<OAuthClient>>,
+    State(identity_repo): State<Arc<IdentityRepository>>,
+    Path(provider): Path<String>,
+    auth_user: Option<AuthUser>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let provider = find_provider(&oauth_client, &provider)?;
+
+    let state = generate_opaque_token();
+    let code_verifier = generate_code_verifier();
+    let expires_at = Utc::now() + Duration::minutes(STATE_TTL_MINUTES);
+
+    // Al vincular, sólo este cliente recibe el nonce: una URL de autorización reenviada a
+    // otra persona no sirve para vincular su identidad a esta cuenta
+    let link_user_id = auth_user.map(|user| user.id);
+    let link_nonce = link_user_id.map(|_| generate_opaque_token());
+
+    if identity_repo.create_state(
+        &hash_token(&state),
+        &provider.name,
+        &code_verifier,
+        link_user_id,
+        link_nonce.as_deref().map(hash_token).as_deref(),
+        expires_at,
+    ).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    let authorization_url = match oauth_client.authorization_url(provider, &state, &code_verifier) {
+        Ok(url) => url,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Proveedor mal configurado"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(
+        OAuthAuthorization { authorization_url, state, link_nonce },
+        "Redirige al usuario a la URL de autorización"
+    )))
+}
+
+pub async fn callback(
+    State(oauth_client): State<Arc<OAuthClient>>,
+    State(identity_repo): State<Arc<IdentityRepository>>,
+    State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    Path(provider): Path<String>,
+    auth_user: Option<AuthUser>,
+    client: ClientInfo,
+    Json(payload): Json<OAuthCallbackRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let provider = find_provider(&oauth_client, &provider)?;
+
+    // El state es de un solo uso y guarda el verificador PKCE de esta autorización
+    let state = match identity_repo.consume_state(&hash_token(&payload.state), &provider.name).await {
+        Ok(Some(state)) => state,
+        Ok(None) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Autorización inválida o expirada"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // La vinculación sólo la completa la misma cuenta, desde el cliente que la inició
+    if let Some(link_user_id) = state.link_user_id {
+        let same_user = auth_user.as_ref().is_some_and(|user| user.id == link_user_id);
+        let same_client = match (&state.link_nonce_hash, &payload.link_nonce) {
+            (Some(expected), Some(nonce)) => hash_token(nonce) == *expected,
+            _ => false,
+        };
+
+        if !same_user || !same_client {
+            tracing::warn!("Vinculación con {} rechazada para la cuenta {}", provider.name, link_user_id);
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::error("Esta autorización pertenece a otra sesión"))
+            ));
+        }
+    }
+
+    let identity = match oauth_client.fetch_identity(provider, &payload.code, &state.code_verifier).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            tracing::warn!("Login con {} fallido: {}", provider.name, e);
+            return Err((
+                StatusCode::BAD_GATEWAY,
+                Json(ApiResponse::error("No se pudo completar el login con el proveedor"))
+            ));
+        }
+    };
+
+    // Vincular el proveedor a una cuenta con sesión iniciada
+    if let Some(user_id) = state.link_user_id {
+        return match identity_repo.link(user_id, &provider.name, &identity.subject, identity.email.as_deref()).await {
+            Ok(Some(linked)) => Ok(Json(ApiResponse::success(
+                OAuthCallbackResponse::Linked(linked),
+                "Cuenta vinculada exitosamente"
+            ))),
+            Ok(None) => Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::error("Esta cuenta del proveedor ya está vinculada a un usuario"))
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error al vincular la cuenta"))
+            ))
+        };
+    }
+
+    let user = match identity_repo.find_user(&provider.name, &identity.subject).await {
+        Ok(user) => user,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if let Some(user) = user {
+        if let Some(pending) = two_factor_challenge(&two_factor_repo, &user).await? {
+            return Ok(Json(ApiResponse::success(
+                OAuthCallbackResponse::TwoFactorRequired(pending),
+                "Se requiere el código de verificación en dos pasos"
+            )));
+        }
+
+        let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
+        return Ok(Json(ApiResponse::success(OAuthCallbackResponse::Authenticated(auth_response), "Login exitoso")));
+    }
+
+    match identity_repo.is_linked(&provider.name, &identity.subject).await {
+        Ok(true) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Cuenta desactivada"))
+        )),
+        Ok(false) => {},
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    // Nunca vincular automáticamente por email: el dueño de la cuenta debe hacerlo con sesión iniciada
+    if let Some(email) = identity.email.as_deref() {
+        if let Ok(Some(_)) = user_repo.find_by_email(email).await {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiResponse::error("Ya existe una cuenta con este email. Inicia sesión y vincula el proveedor desde tu perfil"))
+            ));
+        }
+    }
+
+    // Primer login: guardar la identidad hasta que el usuario elija nombre de usuario
+    let signup_token = generate_opaque_token();
+    let ttl = Duration::minutes(SIGNUP_TTL_MINUTES);
+
+    if identity_repo.create_signup(
+        &hash_token(&signup_token),
+        &provider.name,
+        &identity.subject,
+        identity.email.as_deref(),
+        identity.email_verified,
+        identity.display_name.as_deref(),
+        Utc::now() + ttl,
+    ).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    let since = Utc::now() - username_grace_period();
+    let suggested_username = match identity.username.filter(|username| validate_username(username).is_ok()) {
+        Some(username) => match user_repo.is_username_available(&username, None, since).await {
+            Ok(true) => Some(username),
+            _ => None,
+        },
+        None => None,
+    };
+
+    let pending = OAuthSignupPending {
+        signup_required: true,
+        signup_token,
+        suggested_username,
+        email: identity.email,
+        expires_in: ttl.num_seconds(),
+    };
+
+    Ok(Json(ApiResponse::success(
+        OAuthCallbackResponse::SignupRequired(pending),
+        "Elige un nombre de usuario para completar el registro"
+    )))
+}
+
+pub async fn complete_signup(
+    State(identity_repo): State<Arc<IdentityRepository>>,
+    State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(email_verification_repo): State<Arc<EmailVerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    client: ClientInfo,
+    Json(payload): Json<OAuthSignupRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let signup = match identity_repo.find_signup(&hash_token(&payload.signup_token)).await {
+        Ok(Some(signup)) => signup,
+        Ok(None) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Registro inválido o expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if is_reserved_username(&payload.username) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Ese nombre de usuario está reservado"))
+        ));
+    }
+
+    let Some(email) = payload.email.clone().or_else(|| signup.email.clone()) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("El proveedor no facilitó un email; indica uno para continuar"))
+        ));
+    };
+
+    // Sin distinguir mayúsculas y respetando los nombres retenidos tras un cambio
+    let since = Utc::now() - username_grace_period();
+    match user_repo.is_username_available(&payload.username, None, since).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El nombre de usuario ya está en uso"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    if let Ok(Some(_)) = user_repo.find_by_email(&email).await {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El email ya está registrado"))
+        ));
+    }
+
+    // Contraseña aleatoria que nadie conoce; se puede fijar una con "olvidé mi contraseña"
+    let password_hash = match hash_password(&generate_opaque_token()).await {
+        Ok(hash) => hash,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al crear la cuenta"))
+        ))
+    };
+
+    let user = match identity_repo.complete_signup(&signup, &payload.username, &email, &password_hash).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Registro inválido o expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al crear la cuenta"))
+        ))
+    };
+
+    if user.email_verified_at.is_none() {
+        if let Err(e) = send_verification_email(&email_verification_repo, mailer, &user).await {
+            tracing::error!("Error generando verificación de email: {}", e);
+        }
+    }
+
+    let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(auth_response, "Usuario registrado exitosamente"))
+    ))
+}
+
+pub async fn list_identities(
+    State(identity_repo): State<Arc<IdentityRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match identity_repo.list_for_user(auth_user.id).await {
+        Ok(identities) => Ok(Json(ApiResponse::success(identities, "Cuentas vinculadas obtenidas exitosamente"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las cuentas vinculadas"))
+        ))
+    }
+}
+
+pub async fn unlink_identity(
+    State(identity_repo): State<Arc<IdentityRepository>>,
+    Path(provider): Path<String>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match identity_repo.unlink(auth_user.id, &provider).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Cuenta desvinculada"))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("No hay ninguna cuenta vinculada de ese proveedor"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al desvincular la cuenta"))
+        ))
+    }
+}
+
+fn find_provider<'a>(
+    oauth_client: &'a OAuthClient,
+    name: &str,
+) -> Result<&'a OAuthProvider, (StatusCode, Json<ApiResponse<()>>)> {
+    oauth_client.provider(name).ok_or_else(|| (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::error("Proveedor de login no disponible"))
+    ))
+}
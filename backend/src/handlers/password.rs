@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{ApiResponse, ForgotPasswordRequest, ResetPasswordRequest};
+use crate::auth::{generate_opaque_token, hash_password, hash_token, AccountStateCache};
+use crate::mailer::{EmailMessage, Mailer};
+use crate::repository::{PasswordResetRepository, UserRepository};
+use super::app_url;
+
+pub async fn forgot_password(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(password_reset_repo): State<Arc<PasswordResetRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    // La respuesta es la misma exista o no la cuenta, para no revelar emails registrados
+    let response = Json(ApiResponse::success(
+        (),
+        "Si el email está registrado, recibirás un enlace para restablecer tu contraseña"
+    ));
+
+    let user = match user_repo.find_by_email(&payload.email).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Ok(response),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(reset_ttl_minutes());
+
+    if password_reset_repo.create(user.id, &hash_token(&token), expires_at).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    let message = EmailMessage {
+        to: user.email,
+        subject: "Restablece tu contraseña de Pitaia".to_string(),
+        body: format!(
+            "Hola {},\n\nPara restablecer tu contraseña abre este enlace (válido por {} minutos):\n{}/reset-password?token={}\n\nSi no lo solicitaste, ignora este correo.",
+            user.username,
+            reset_ttl_minutes(),
+            app_url(),
+            token
+        ),
+    };
+
+    // Enviar en segundo plano para que el tiempo de respuesta no delate la existencia de la cuenta
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Error enviando correo de recuperación: {}", e);
+        }
+    });
+
+    Ok(response)
+}
+
+pub async fn reset_password(
+    State(password_reset_repo): State<Arc<PasswordResetRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let password_hash = match hash_password(&payload.new_password).await {
+        Ok(hash) => hash,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    match password_reset_repo.reset_password(&hash_token(&payload.token), &password_hash).await {
+        Ok(Some(user_id)) => {
+            account_cache.invalidate_user(user_id);
+            Ok(Json(ApiResponse::success((), "Contraseña restablecida exitosamente")))
+        },
+        Ok(None) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("El enlace de recuperación es inválido o ha expirado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al restablecer la contraseña"))
+        ))
+    }
+}
+
+fn reset_ttl_minutes() -> i64 {
+    std::env::var("PASSWORD_RESET_TTL_MINUTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(60)
+}
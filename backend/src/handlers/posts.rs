@@ -8,9 +8,12 @@ use std::sync::Arc;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::models::{ApiResponse, CreatePost, PostWithUser};
-use crate::repository::PostRepository;
-use crate::middleware::AuthUser;
+use crate::models::{ApiResponse, CreatePost, PostWithUser, ProfilePosts};
+use crate::repository::{BlockRepository, FollowRepository, PostRepository, SuggestionRepository, UserRepository};
+use crate::middleware::{AuthUser, VerifiedUser};
+use super::blocks::ensure_not_blocked;
+use super::follows::{check_can_view, FollowListQuery};
+use super::suggestions::refresh_suggestions;
 
 #[derive(Deserialize)]
 pub struct FeedQuery {
@@ -20,7 +23,7 @@ pub struct FeedQuery {
 
 pub async fn create_post(
     State(post_repo): State<Arc<PostRepository>>,
-    auth_user: AuthUser,
+    auth_user: VerifiedUser,
     Json(payload): Json<CreatePost>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
     if let Err(validation_errors) = payload.validate() {
@@ -66,9 +69,23 @@ pub async fn get_feed(
 
 pub async fn toggle_like(
     State(post_repo): State<Arc<PostRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
     Path(post_id): Path<Uuid>,
     auth_user: AuthUser,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    // Los posts de cuentas privadas no se pueden ver ni tocar sin seguirlas
+    match post_repo.is_visible(post_id, Some(auth_user.id)).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Post no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al procesar like"))
+        ))
+    }
+
     let is_liked = match post_repo.toggle_like(auth_user.id, post_id).await {
         Ok(liked) => liked,
         Err(_) => return Err((
@@ -76,7 +93,125 @@ pub async fn toggle_like(
             Json(ApiResponse::error("Error al procesar like"))
         ))
     };
+    refresh_suggestions(&suggestion_repo, auth_user.id).await;
 
     let message = if is_liked { "Like agregado" } else { "Like removido" };
     Ok(Json(ApiResponse::success(is_liked, message)))
 }
+
+/// Posts de un perfil: primero los fijados y después la cronología paginada.
+pub async fn list_user_posts(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(post_repo): State<Arc<PostRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: Option<AuthUser>,
+    Path(username): Path<String>,
+    Query(params): Query<FollowListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let author = match user_repo.find_by_username(&username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let viewer_id = auth_user.map(|u| u.id);
+    ensure_not_blocked(&block_repo, viewer_id, author.id).await?;
+    check_can_view(&follow_repo, viewer_id, &author).await?;
+
+    // Los fijados sólo encabezan la primera página
+    let pinned = if params.offset() == 0 {
+        match post_repo.pinned_posts(author.id, viewer_id).await {
+            Ok(pinned) => pinned,
+            Err(_) => return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error al obtener los posts"))
+            ))
+        }
+    } else {
+        Vec::new()
+    };
+
+    let posts = match post_repo.user_timeline(author.id, viewer_id, params.limit(), params.offset()).await {
+        Ok(posts) => posts,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener los posts"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(ProfilePosts { pinned, posts }, "Posts obtenidos exitosamente")))
+}
+
+pub async fn pin_post(
+    State(post_repo): State<Arc<PostRepository>>,
+    Path(post_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    find_own_post(&post_repo, post_id, &auth_user).await?;
+
+    let max_pinned = max_pinned_posts();
+    match post_repo.pin(auth_user.id, post_id, max_pinned).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Post fijado en tu perfil"))),
+        Ok(false) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error(&format!(
+                "Sólo puedes fijar {} posts. Desfija alguno antes de fijar otro",
+                max_pinned
+            )))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al fijar el post"))
+        ))
+    }
+}
+
+pub async fn unpin_post(
+    State(post_repo): State<Arc<PostRepository>>,
+    Path(post_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    find_own_post(&post_repo, post_id, &auth_user).await?;
+
+    match post_repo.unpin(auth_user.id, post_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Post desfijado"))),
+        Ok(false) => Ok(Json(ApiResponse::success((), "El post no estaba fijado"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al desfijar el post"))
+        ))
+    }
+}
+
+// Sólo el autor puede fijar sus posts; los ajenos se tratan como inexistentes
+async fn find_own_post(
+    post_repo: &PostRepository,
+    post_id: Uuid,
+    auth_user: &AuthUser,
+) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    match post_repo.find_by_id(post_id).await {
+        Ok(Some(post)) if post.user_id == auth_user.id => Ok(()),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Post no encontrado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+fn max_pinned_posts() -> i64 {
+    std::env::var("PINNED_POSTS_MAX")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(3)
+}
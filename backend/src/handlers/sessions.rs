@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{ApiResponse, SessionInfo};
+use crate::auth::AccountStateCache;
+use crate::repository::SessionRepository;
+use crate::middleware::AuthUser;
+
+pub async fn list_sessions(
+    State(session_repo): State<Arc<SessionRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let sessions = match session_repo.list_active(auth_user.id).await {
+        Ok(sessions) => sessions,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las sesiones"))
+        ))
+    };
+
+    let sessions: Vec<SessionInfo> = sessions
+        .into_iter()
+        .map(|session| SessionInfo::from_session(session, auth_user.session_id))
+        .collect();
+
+    Ok(Json(ApiResponse::success(sessions, "Sesiones obtenidas exitosamente")))
+}
+
+pub async fn revoke_session(
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(account_cache): State<Arc<AccountStateCache>>,
+    Path(session_id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match session_repo.revoke(session_id, auth_user.id).await {
+        Ok(true) => {
+            account_cache.invalidate_user(auth_user.id);
+            Ok(Json(ApiResponse::success((), "Sesión revocada")))
+        },
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Sesión no encontrada"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al revocar la sesión"))
+        ))
+    }
+}
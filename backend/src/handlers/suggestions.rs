@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::ApiResponse;
+use crate::repository::{SuggestionRepository, UserRepository};
+use crate::middleware::AuthUser;
+use crate::suggestions;
+
+#[derive(Deserialize)]
+pub struct SuggestionQuery {
+    pub limit: Option<i64>,
+}
+
+pub async fn list_suggestions(
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Query(params): Query<SuggestionQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+
+    let computed_at = match suggestion_repo.computed_at(auth_user.id).await {
+        Ok(computed_at) => computed_at,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las sugerencias"))
+        ))
+    };
+
+    // Sin cálculo previo o con uno antiguo se encola; el worker lo rehará en segundo plano
+    let stale = computed_at.is_none_or(|at| Utc::now() - at > suggestions::max_age());
+    if stale {
+        refresh_suggestions(&suggestion_repo, auth_user.id).await;
+    }
+
+    // Mientras tanto, las cuentas más seguidas
+    let result = match computed_at {
+        Some(_) => suggestion_repo.for_user(auth_user.id, limit).await,
+        None => suggestion_repo.popular(auth_user.id, limit).await,
+    };
+
+    match result {
+        Ok(suggestions) => Ok(Json(ApiResponse::success(suggestions, "Sugerencias obtenidas exitosamente"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al obtener las sugerencias"))
+        ))
+    }
+}
+
+pub async fn dismiss_suggestion(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(suggestion_repo): State<Arc<SuggestionRepository>>,
+    auth_user: AuthUser,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let target = match user_repo.find_by_username(&username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    match suggestion_repo.dismiss(auth_user.id, target.id).await {
+        Ok(()) => Ok(Json(ApiResponse::success((), "Sugerencia descartada"))),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al descartar la sugerencia"))
+        ))
+    }
+}
+
+/// Encola el recálculo tras un cambio en el grafo del usuario; un fallo no debe
+/// afectar a la acción que lo provocó.
+pub(crate) async fn refresh_suggestions(suggestion_repo: &SuggestionRepository, user_id: Uuid) {
+    if let Err(e) = suggestion_repo.mark_stale(user_id).await {
+        tracing::warn!("No se pudo encolar el cálculo de sugerencias: {}", e);
+    }
+}
+
+/// Tras un nuevo seguimiento cambian también los candidatos de los seguidores de quien sigue.
+pub(crate) async fn refresh_follower_suggestions(suggestion_repo: &SuggestionRepository, user_id: Uuid) {
+    if let Err(e) = suggestion_repo.mark_followers_stale(user_id).await {
+        tracing::warn!("No se pudo encolar el cálculo de sugerencias de los seguidores: {}", e);
+    }
+}
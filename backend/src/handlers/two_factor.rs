@@ -0,0 +1,281 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::models::{
+    ApiResponse, RecoveryCodes, TotpCodeRequest, TotpEnrollment, TwoFactorLoginRequest, UserTotp,
+};
+use crate::auth::{hash_token, totp, JwtService};
+use crate::middleware::{AuthUser, ClientInfo};
+use crate::repository::{
+    RefreshTokenRepository, RoleRepository, SessionRepository, TwoFactorRepository, UserRepository,
+};
+use crate::throttle::{LoginBlock, LoginGuard};
+use super::auth::{login_block_response, start_session};
+
+const RECOVERY_CODES_COUNT: usize = 10;
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+pub async fn enroll(
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let secret = totp::generate_secret();
+
+    match two_factor_repo.start_enrollment(auth_user.id, &secret).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("La verificación en dos pasos ya está activa"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    let totp = match totp::build(&secret, &auth_user.username) {
+        Ok(totp) => totp,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let qr_code = match totp.get_qr_base64() {
+        Ok(qr_code) => qr_code,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error generando el código QR"))
+        ))
+    };
+
+    let enrollment = TotpEnrollment {
+        otpauth_url: totp.get_url(),
+        secret,
+        qr_code,
+    };
+
+    Ok(Json(ApiResponse::success(enrollment, "Escanea el código QR y confirma con un código")))
+}
+
+pub async fn confirm(
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    auth_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let pending = match two_factor_repo.find(auth_user.id).await {
+        Ok(Some(totp)) if totp.confirmed_at.is_none() => totp,
+        Ok(Some(_)) => return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("La verificación en dos pasos ya está activa"))
+        )),
+        Ok(None) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("No hay un alta de verificación en dos pasos pendiente"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let step = match totp_step(&pending, &auth_user.username, &payload.code) {
+        Some(step) => step,
+        None => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Código incorrecto"))
+        ))
+    };
+
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODES_COUNT);
+    let hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_token(&totp::normalize_recovery_code(code)))
+        .collect();
+
+    if two_factor_repo.confirm(auth_user.id, step, &hashes).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    Ok(Json(ApiResponse::success(
+        RecoveryCodes { recovery_codes },
+        "Verificación en dos pasos activada. Guarda tus códigos de recuperación"
+    )))
+}
+
+pub async fn disable(
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    auth_user: AuthUser,
+    Json(payload): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let active = match two_factor_repo.find(auth_user.id).await {
+        Ok(Some(totp)) if totp.confirmed_at.is_some() => totp,
+        Ok(_) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("La verificación en dos pasos no está activa"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    match check_second_factor(&two_factor_repo, &active, &auth_user.username, &payload.code).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Código incorrecto"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    if two_factor_repo.disable(auth_user.id).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ));
+    }
+
+    Ok(Json(ApiResponse::success((), "Verificación en dos pasos desactivada")))
+}
+
+// Segundo paso del login: canjea el reto + código por el par de tokens
+pub async fn verify_login(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(refresh_token_repo): State<Arc<RefreshTokenRepository>>,
+    State(session_repo): State<Arc<SessionRepository>>,
+    State(jwt_service): State<Arc<JwtService>>,
+    State(role_repo): State<Arc<RoleRepository>>,
+    State(two_factor_repo): State<Arc<TwoFactorRepository>>,
+    State(login_guard): State<Arc<LoginGuard>>,
+    client: ClientInfo,
+    Json(payload): Json<TwoFactorLoginRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let challenge = match two_factor_repo
+        .attempt_challenge(&hash_token(&payload.challenge_token), MAX_CHALLENGE_ATTEMPTS)
+        .await
+    {
+        Ok(Some(challenge)) => challenge,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("El reto de verificación es inválido o ha expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // Las cuentas pendientes de eliminación también completan el login (y la recuperan)
+    let user = match user_repo.find_by_id_for_login(challenge.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("El reto de verificación es inválido o ha expirado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // El segundo factor cuenta contra los mismos límites que la contraseña
+    let ip_address = client.ip_address.as_deref();
+    match login_guard.check(&user.username, ip_address).await {
+        Ok(Some(block)) => return Err(login_block_response(block)),
+        Ok(None) => {},
+        Err(e) => tracing::error!("Error consultando intentos de login: {}", e),
+    }
+
+    let active = match two_factor_repo.find(user.id).await {
+        Ok(Some(totp)) if totp.confirmed_at.is_some() => totp,
+        Ok(_) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("La verificación en dos pasos no está activa"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    match check_second_factor(&two_factor_repo, &active, &user.username, &payload.code).await {
+        Ok(true) => {},
+        Ok(false) => {
+            return match login_guard.record_failure(&user.username, ip_address, Some(user.id)).await {
+                Ok(Some(block @ LoginBlock::AccountLocked { .. })) => Err(login_block_response(block)),
+                Ok(_) => Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error("Código incorrecto"))
+                )),
+                Err(e) => {
+                    tracing::error!("Error registrando intento de login fallido: {}", e);
+                    Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiResponse::error("Código incorrecto"))
+                    ))
+                }
+            };
+        },
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    // El reto es de un solo uso
+    match two_factor_repo.consume_challenge(challenge.id).await {
+        Ok(true) => {},
+        Ok(false) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("El reto de verificación ya fue usado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+
+    let username = user.username.clone();
+    let auth_response = start_session(&user_repo, &jwt_service, &session_repo, &refresh_token_repo, &role_repo, user, &client).await?;
+
+    if let Err(e) = login_guard.record_success(&username).await {
+        tracing::error!("Error reiniciando intentos de login: {}", e);
+    }
+
+    Ok(Json(ApiResponse::success(auth_response, "Login exitoso")))
+}
+
+fn totp_step(totp: &UserTotp, username: &str, code: &str) -> Option<i64> {
+    let totp = totp::build(&totp.secret, username).ok()?;
+    totp::verify_code(&totp, code)
+}
+
+// Acepta un código TOTP (no reutilizado) o un código de recuperación sin usar
+async fn check_second_factor(
+    two_factor_repo: &TwoFactorRepository,
+    totp: &UserTotp,
+    username: &str,
+    code: &str,
+) -> anyhow::Result<bool> {
+    if let Some(step) = totp_step(totp, username, code) {
+        return two_factor_repo.record_step(totp.user_id, step).await;
+    }
+
+    let recovery_code = totp::normalize_recovery_code(code);
+    if recovery_code.is_empty() {
+        return Ok(false);
+    }
+
+    two_factor_repo.use_recovery_code(totp.user_id, &hash_token(&recovery_code)).await
+}
@@ -1,18 +1,27 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Redirect, Response},
     Json,
 };
+use validator::Validate;
 use std::sync::Arc;
+use serde::Deserialize;
+use chrono::{Datelike, NaiveDate, Utc};
 
-use crate::models::{ApiResponse, UserProfile};
-use crate::repository::UserRepository;
+use crate::models::{ApiResponse, PrivateUserProfile, UpdateProfileRequest, UserProfile};
+use crate::repository::{BlockRepository, FollowRepository, UserRepository};
+use crate::middleware::AuthUser;
+use super::account::username_grace_period;
+use super::blocks::ensure_not_blocked;
 
 pub async fn get_user_profile(
     State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    State(block_repo): State<Arc<BlockRepository>>,
+    auth_user: Option<AuthUser>,
     Path(username): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+) -> Result<Response, (StatusCode, Json<ApiResponse<()>>)> {
     if username.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
@@ -21,6 +30,99 @@ pub async fn get_user_profile(
     }
 
     let user = match user_repo.find_by_username(&username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            // Un nombre cambiado hace poco redirige al actual; temporal, porque el nombre se libera
+            return match user_repo.find_renamed(&username, Utc::now() - username_grace_period()).await {
+                Ok(Some(current)) => Ok(Redirect::temporary(&format!("/api/users/{}", current)).into_response()),
+                Ok(None) => Err((
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::error("Usuario no encontrado"))
+                )),
+                Err(_) => Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error("Error del servidor"))
+                ))
+            };
+        },
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    let user_id = user.id;
+    ensure_not_blocked(&block_repo, auth_user.as_ref().map(|viewer| viewer.id), user_id).await?;
+    let mut user_profile: UserProfile = user.into();
+
+    // Con sesión, el perfil ajeno indica la relación de seguimiento con quien lo mira
+    if let Some(viewer) = auth_user.filter(|viewer| viewer.id != user_id) {
+        match follow_repo.relationship(viewer.id, user_id).await {
+            Ok(relationship) => user_profile = user_profile.with_relationship(relationship),
+            Err(_) => return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error del servidor"))
+            ))
+        }
+    }
+
+    Ok(Json(ApiResponse::success(user_profile, "Perfil obtenido exitosamente")).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub async fn search_users(
+    State(user_repo): State<Arc<UserRepository>>,
+    auth_user: Option<AuthUser>,
+    Query(params): Query<SearchQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let query = search_term(params.q.as_deref())?;
+    let limit = params.limit.unwrap_or(20).clamp(1, 50);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let viewer_id = auth_user.map(|u| u.id);
+
+    let results = match user_repo.search(query, viewer_id, limit, offset).await {
+        Ok(results) => results,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al buscar usuarios"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(results, "Búsqueda completada")))
+}
+
+/// Sugerencias para menciones mientras se escribe; admite el prefijo con o sin `@`.
+pub async fn autocomplete_users(
+    State(user_repo): State<Arc<UserRepository>>,
+    auth_user: Option<AuthUser>,
+    Query(params): Query<SearchQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let prefix = search_term(params.q.as_deref().map(|q| q.trim_start_matches('@')))?;
+    let limit = params.limit.unwrap_or(8).clamp(1, 20);
+    let viewer_id = auth_user.map(|u| u.id);
+
+    let results = match user_repo.autocomplete(prefix, viewer_id, limit).await {
+        Ok(results) => results,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al buscar usuarios"))
+        ))
+    };
+
+    Ok(Json(ApiResponse::success(results, "Sugerencias obtenidas")))
+}
+
+pub async fn get_my_profile(
+    State(user_repo): State<Arc<UserRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = match user_repo.find_by_id(auth_user.id).await {
         Ok(Some(user)) => user,
         Ok(None) => return Err((
             StatusCode::NOT_FOUND,
@@ -32,6 +134,118 @@ pub async fn get_user_profile(
         ))
     };
 
-    let user_profile: UserProfile = user.into();
+    let user_profile: PrivateUserProfile = user.into();
     Ok(Json(ApiResponse::success(user_profile, "Perfil obtenido exitosamente")))
 }
+
+pub async fn update_my_profile(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(follow_repo): State<Arc<FollowRepository>>,
+    auth_user: AuthUser,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    // El validador de URLs acepta cualquier esquema (javascript:, data:...)
+    if let Some(Some(website)) = &payload.website {
+        let scheme_ok = url::Url::parse(website.trim())
+            .map(|url| url.scheme() == "http" || url.scheme() == "https")
+            .unwrap_or(false);
+
+        if !scheme_ok {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("El sitio web debe ser una URL http o https"))
+            ));
+        }
+    }
+
+    if let Some(Some(birthday)) = payload.birthday {
+        if !is_plausible_birthday(birthday) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Fecha de nacimiento inválida"))
+            ));
+        }
+    }
+
+    let mut user = match user_repo.find_by_id(auth_user.id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    apply_text_change(&mut user.display_name, payload.display_name);
+    apply_text_change(&mut user.bio, payload.bio);
+    apply_text_change(&mut user.website, payload.website);
+    apply_text_change(&mut user.location, payload.location);
+    apply_text_change(&mut user.pronouns, payload.pronouns);
+
+    if let Some(birthday) = payload.birthday {
+        user.birthday = birthday;
+    }
+    if let Some(visibility) = payload.birthday_visibility {
+        user.birthday_visibility = visibility.as_str().to_string();
+    }
+
+    let was_private = user.is_private;
+    if let Some(is_private) = payload.is_private {
+        user.is_private = is_private;
+    }
+
+    let user = match user_repo.update_profile(&user).await {
+        Ok(user) => user,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al actualizar el perfil"))
+        ))
+    };
+
+    // Al hacerse pública, las solicitudes pendientes pasan a ser seguidores
+    if was_private && !user.is_private && follow_repo.approve_all_requests(user.id).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al aprobar las solicitudes pendientes"))
+        ));
+    }
+
+    let user_profile: PrivateUserProfile = user.into();
+    Ok(Json(ApiResponse::success(user_profile, "Perfil actualizado")))
+}
+
+fn search_term(query: Option<&str>) -> Result<&str, (StatusCode, Json<ApiResponse<()>>)> {
+    let query = query.map(str::trim).unwrap_or_default();
+
+    if query.is_empty() || query.chars().count() > 100 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("La búsqueda debe tener entre 1 y 100 caracteres"))
+        ));
+    }
+
+    Ok(query)
+}
+
+// Campo ausente: sin cambios; null o texto en blanco: se borra
+fn apply_text_change(field: &mut Option<String>, change: Option<Option<String>>) {
+    if let Some(value) = change {
+        *field = value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+}
+
+fn is_plausible_birthday(birthday: NaiveDate) -> bool {
+    birthday.year() >= 1900 && birthday <= Utc::now().date_naive()
+}
@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{ApiResponse, User, VerifyEmailRequest};
+use crate::auth::{generate_opaque_token, hash_token};
+use crate::mailer::{EmailMessage, Mailer};
+use crate::middleware::AuthUser;
+use crate::repository::{EmailVerificationRepository, UserRepository};
+use super::app_url;
+
+pub async fn verify_email(
+    State(email_verification_repo): State<Arc<EmailVerificationRepository>>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match email_verification_repo.verify(&hash_token(&payload.token)).await {
+        Ok(Some(_)) => Ok(Json(ApiResponse::success((), "Email verificado exitosamente"))),
+        Ok(None) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("El enlace de verificación es inválido o ha expirado"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al verificar el email"))
+        ))
+    }
+}
+
+pub async fn resend_verification(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(email_verification_repo): State<Arc<EmailVerificationRepository>>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    let user = match user_repo.find_by_id(auth_user.id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if user.email_verified_at.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("El email ya está verificado"))
+        ));
+    }
+
+    // Evitar reenvíos en ráfaga
+    let last_sent_at = match email_verification_repo.last_sent_at(user.id).await {
+        Ok(last_sent_at) => last_sent_at,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if let Some(last_sent_at) = last_sent_at {
+        let available_at = last_sent_at + Duration::seconds(resend_cooldown_seconds());
+        if available_at > Utc::now() {
+            let wait = (available_at - Utc::now()).num_seconds().max(1);
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::error(&format!("Espera {} segundos antes de solicitar otro correo", wait)))
+            ));
+        }
+    }
+
+    if send_verification_email(&email_verification_repo, mailer, &user).await.is_err() {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al enviar el correo de verificación"))
+        ));
+    }
+
+    Ok(Json(ApiResponse::success((), "Correo de verificación enviado")))
+}
+
+/// Genera un token de verificación para el email actual del usuario y lo envía en segundo plano.
+pub(crate) async fn send_verification_email(
+    email_verification_repo: &EmailVerificationRepository,
+    mailer: Arc<dyn Mailer>,
+    user: &User,
+) -> anyhow::Result<()> {
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::hours(verification_ttl_hours());
+
+    email_verification_repo
+        .create(user.id, &user.email, &hash_token(&token), expires_at, false)
+        .await?;
+
+    let message = EmailMessage {
+        to: user.email.clone(),
+        subject: "Verifica tu email en Pitaia".to_string(),
+        body: format!(
+            "Hola {},\n\nConfirma tu email abriendo este enlace (válido por {} horas):\n{}/verify-email?token={}\n",
+            user.username,
+            verification_ttl_hours(),
+            app_url(),
+            token
+        ),
+    };
+
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(message).await {
+            tracing::error!("Error enviando correo de verificación: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Envía a la dirección nueva el enlace que confirma el cambio de email y avisa a la actual.
+pub(crate) async fn send_email_change_confirmation(
+    email_verification_repo: &EmailVerificationRepository,
+    mailer: Arc<dyn Mailer>,
+    user: &User,
+    new_email: &str,
+) -> anyhow::Result<()> {
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::hours(verification_ttl_hours());
+
+    email_verification_repo
+        .create(user.id, new_email, &hash_token(&token), expires_at, true)
+        .await?;
+
+    let confirmation = EmailMessage {
+        to: new_email.to_string(),
+        subject: "Confirma tu nuevo email en Pitaia".to_string(),
+        body: format!(
+            "Hola {},\n\nConfirma que quieres usar esta dirección en tu cuenta abriendo este enlace (válido por {} horas):\n{}/verify-email?token={}\n",
+            user.username,
+            verification_ttl_hours(),
+            app_url(),
+            token
+        ),
+    };
+
+    let notice = EmailMessage {
+        to: user.email.clone(),
+        subject: "Cambio de email solicitado en Pitaia".to_string(),
+        body: format!(
+            "Hola {},\n\nSe ha solicitado cambiar el email de tu cuenta a {}. El cambio sólo se aplicará cuando se confirme desde esa dirección.\n\nSi no fuiste tú, cambia tu contraseña y cierra tus sesiones.\n",
+            user.username,
+            new_email
+        ),
+    };
+
+    tokio::spawn(async move {
+        for message in [confirmation, notice] {
+            if let Err(e) = mailer.send(message).await {
+                tracing::error!("Error enviando correo de cambio de email: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn verification_ttl_hours() -> i64 {
+    std::env::var("EMAIL_VERIFICATION_TTL_HOURS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(48)
+}
+
+fn resend_cooldown_seconds() -> i64 {
+    std::env::var("EMAIL_VERIFICATION_RESEND_COOLDOWN_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(60)
+}
@@ -0,0 +1,124 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+
+use crate::models::{ApiResponse, CreateVerificationRequest};
+use crate::repository::{UserRepository, VerificationRepository};
+use crate::middleware::{AuthUser, VerifiedUser};
+
+pub async fn request_verification(
+    State(user_repo): State<Arc<UserRepository>>,
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    verified_user: VerifiedUser,
+    Json(payload): Json<CreateVerificationRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    if let Err(validation_errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Datos inválidos: {:?}", validation_errors)))
+        ));
+    }
+
+    let user = match user_repo.find_by_id(verified_user.id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Usuario no encontrado"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if user.verification_badge.as_deref() == Some(payload.badge_type.as_str()) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("Tu cuenta ya tiene esa verificación"))
+        ));
+    }
+
+    let latest = match verification_repo.latest_for_user(user.id).await {
+        Ok(latest) => latest,
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    // Tras un rechazo hay que esperar antes de volver a intentarlo
+    if let Some(latest) = latest.filter(|latest| latest.status == "rejected") {
+        let available_at = latest.reviewed_at.unwrap_or(latest.created_at)
+            + Duration::days(verification_reapply_days());
+        if available_at > Utc::now() {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::error(&format!(
+                    "Podrás volver a solicitar la verificación a partir del {}",
+                    available_at.format("%d/%m/%Y")
+                )))
+            ));
+        }
+    }
+
+    match verification_repo.create(user.id, &payload).await {
+        Ok(Some(request)) => Ok((
+            StatusCode::CREATED,
+            Json(ApiResponse::success(request, "Solicitud de verificación enviada"))
+        )),
+        Ok(None) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::error("Ya tienes una solicitud de verificación pendiente"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al enviar la solicitud"))
+        ))
+    }
+}
+
+pub async fn verification_status(
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match verification_repo.latest_for_user(auth_user.id).await {
+        Ok(Some(request)) => Ok(Json(ApiResponse::success(request, "Estado de la solicitud"))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("No has solicitado la verificación"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    }
+}
+
+pub async fn cancel_verification_request(
+    State(verification_repo): State<Arc<VerificationRepository>>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
+    match verification_repo.cancel(auth_user.id).await {
+        Ok(true) => Ok(Json(ApiResponse::success((), "Solicitud retirada"))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("No tienes ninguna solicitud pendiente"))
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error al retirar la solicitud"))
+        ))
+    }
+}
+
+fn verification_reapply_days() -> i64 {
+    std::env::var("VERIFICATION_REAPPLY_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(30)
+}
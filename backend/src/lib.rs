@@ -1,8 +1,17 @@
 pub mod auth;
 pub mod database;
+pub mod exports;
 pub mod handlers;
+pub mod mailer;
+pub mod media;
 pub mod middleware;
 pub mod models;
+pub mod oauth;
 pub mod repository;
+pub mod state;
+pub mod storage;
+pub mod suggestions;
+pub mod throttle;
+pub mod users;
 
 pub use models::*;
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use axum::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use lettre::{
+    message::Mailbox,
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::auth::is_production;
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: EmailMessage) -> Result<()>;
+}
+
+/// Construye el mailer según `MAILER` (`smtp` o `log`, por defecto `log`).
+/// En producción `log` no se admite: los correos llevan tokens de acceso que acabarían en los logs.
+pub fn from_env() -> Result<Arc<dyn Mailer>> {
+    let kind = std::env::var("MAILER").unwrap_or_else(|_| "log".to_string());
+
+    match kind.as_str() {
+        "smtp" => Ok(Arc::new(SmtpMailer::from_env()?)),
+        "log" if is_production() => Err(anyhow::anyhow!(
+            "MAILER=log no está permitido en producción; configura MAILER=smtp"
+        )),
+        "log" => Ok(Arc::new(LogMailer::from_env())),
+        other => Err(anyhow::anyhow!("MAILER desconocido: {}", other)),
+    }
+}
+
+// Envío real por SMTP
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env() -> Result<Self> {
+        let host = std::env::var("SMTP_HOST")
+            .map_err(|_| anyhow::anyhow!("SMTP_HOST debe estar definida"))?;
+        let from = std::env::var("MAIL_FROM")
+            .unwrap_or_else(|_| "Pitaia <no-reply@pitaia.com>".to_string());
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?;
+
+        if let Ok(port) = std::env::var("SMTP_PORT") {
+            builder = builder.port(port.parse()?);
+        }
+
+        if let (Ok(username), Ok(password)) = (
+            std::env::var("SMTP_USERNAME"),
+            std::env::var("SMTP_PASSWORD"),
+        ) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(message.to.parse()?)
+            .subject(message.subject)
+            .body(message.body)?;
+
+        self.transport.send(email).await?;
+        Ok(())
+    }
+}
+
+// Mailer para desarrollo y pruebas: registra el correo y opcionalmente lo guarda en `MAIL_OUTBOX_DIR`
+pub struct LogMailer {
+    outbox: Option<PathBuf>,
+}
+
+impl LogMailer {
+    pub fn new(outbox: Option<PathBuf>) -> Self {
+        Self { outbox }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("MAIL_OUTBOX_DIR").ok().map(PathBuf::from))
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        tracing::info!("📧 Correo para {}: {}\n{}", message.to, message.subject, message.body);
+
+        if let Some(outbox) = &self.outbox {
+            tokio::fs::create_dir_all(outbox).await?;
+
+            let file_name = format!(
+                "{}-{}.txt",
+                chrono::Utc::now().format("%Y%m%d%H%M%S%3f"),
+                uuid::Uuid::new_v4()
+            );
+            let contents = format!(
+                "To: {}\nSubject: {}\n\n{}\n",
+                message.to, message.subject, message.body
+            );
+
+            tokio::fs::write(outbox.join(file_name), contents).await?;
+        }
+
+        Ok(())
+    }
+}
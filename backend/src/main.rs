@@ -1,26 +1,42 @@
 use axum::{
-    routing::{get, post},
-    Router,
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post},
+    Extension, Router,
     response::Json,
-    middleware,
-    extract::Request,
 };
 use tower::ServiceBuilder;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
 use tracing_subscriber;
 use std::net::SocketAddr;
-use std::sync::Arc;
 
 mod auth;
 mod database;
+mod exports;
 mod handlers;
+mod mailer;
+mod media;
 mod middleware;
 mod models;
+mod oauth;
 mod repository;
+mod state;
+mod storage;
+mod suggestions;
+mod throttle;
+mod users;
 
-use handlers::{auth as auth_handlers, posts as post_handlers, users as user_handlers};
-use repository::{UserRepository, PostRepository};
+use handlers::{
+    access_tokens as access_token_handlers, account as account_handlers, admin as admin_handlers,
+    auth as auth_handlers, blocks as block_handlers, exports as export_handlers,
+    follows as follow_handlers, magic_link as magic_link_handlers, media as media_handlers,
+    oauth as oauth_handlers, password as password_handlers, posts as post_handlers,
+    sessions as session_handlers, suggestions as suggestion_handlers,
+    two_factor as two_factor_handlers, users as user_handlers, verification as verification_handlers,
+    verified_accounts as verified_account_handlers,
+};
+use auth::scopes::{RequiredScope, Scope};
 use models::ApiResponse;
+use state::AppState;
 
 #[tokio::main]
 async fn main() {
@@ -44,31 +60,139 @@ async fn main() {
         }
     };
     
-    // Crear repositorios
-    let user_repo = Arc::new(UserRepository::new(pool.clone()));
-    let post_repo = Arc::new(PostRepository::new(pool.clone()));
+    // Validar configuración de hashing de contraseñas
+    if let Err(e) = auth::password::PasswordHasher::from_env() {
+        eprintln!("❌ Error en la configuración de contraseñas: {}", e);
+        std::process::exit(1);
+    }
+
+    // Configurar envío de correos
+    let mailer = match mailer::from_env() {
+        Ok(mailer) => mailer,
+        Err(e) => {
+            eprintln!("❌ Error configurando el mailer: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Crear estado compartido (repositorios y servicios)
+    let state = match AppState::new(pool, mailer).await {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("❌ Error inicializando servicios: {}", e);
+            std::process::exit(1);
+        }
+    };
     
+    // Purgar en segundo plano las cuentas cuyo periodo de gracia terminó
+    users::spawn_deletion_purge(state.user_repo.clone(), state.storage.clone());
+
+    // Generar en segundo plano las exportaciones de datos solicitadas
+    exports::ExportWorker::from_env(
+        state.export_repo.clone(),
+        state.user_repo.clone(),
+        state.mailer.clone(),
+        state.storage.clone(),
+    ).spawn();
+
+    // Recalcular en segundo plano las sugerencias de a quién seguir
+    suggestions::SuggestionWorker::from_env(state.suggestion_repo.clone()).spawn();
+
     // Crear router principal
     let app = Router::new()
         // Rutas públicas
         .route("/", get(health_check))
         .route("/health", get(health_check))
+        .route("/.well-known/jwks.json", get(auth_handlers::jwks))
         
         // Rutas de autenticación (públicas)
         .route("/api/auth/register", post(auth_handlers::register))
         .route("/api/auth/login", post(auth_handlers::login))
+        .route("/api/auth/refresh", post(auth_handlers::refresh))
+        .route("/api/auth/logout", post(auth_handlers::logout))
+        .route("/api/auth/logout/all", post(auth_handlers::logout_all))
+        .route("/api/auth/password/forgot", post(password_handlers::forgot_password))
+        .route("/api/auth/password/reset", post(password_handlers::reset_password))
+        .route("/api/auth/email/verify", post(verification_handlers::verify_email))
+        .route("/api/auth/email/resend", post(verification_handlers::resend_verification))
+        .route("/api/auth/2fa/enroll", post(two_factor_handlers::enroll))
+        .route("/api/auth/2fa/confirm", post(two_factor_handlers::confirm))
+        .route("/api/auth/2fa/disable", post(two_factor_handlers::disable))
+        .route("/api/auth/2fa/verify", post(two_factor_handlers::verify_login))
+        .route("/api/auth/sessions", get(session_handlers::list_sessions))
+        .route("/api/auth/sessions/:id", delete(session_handlers::revoke_session))
+        .route("/api/auth/tokens", get(access_token_handlers::list_tokens))
+        .route("/api/auth/tokens", post(access_token_handlers::create_token))
+        .route("/api/auth/tokens/:id", delete(access_token_handlers::revoke_token))
+        .route("/api/auth/magic-link", post(magic_link_handlers::request_link))
+        .route("/api/auth/magic-link/verify", post(magic_link_handlers::login_with_link))
+        .route("/api/auth/oauth/providers", get(oauth_handlers::list_providers))
+        .route("/api/auth/oauth/signup", post(oauth_handlers::complete_signup))
+        .route("/api/auth/oauth/:provider/authorize", post(oauth_handlers::authorize))
+        .route("/api/auth/oauth/:provider/callback", post(oauth_handlers::callback))
+        .route("/api/auth/identities", get(oauth_handlers::list_identities))
+        .route("/api/auth/identities/:provider", delete(oauth_handlers::unlink_identity))
         
-        // Rutas de posts
-        .route("/api/posts", get(post_handlers::get_feed))
-        .route("/api/posts", post(post_handlers::create_post))
-        .route("/api/posts/:id/like", post(post_handlers::toggle_like))
+        // Rutas de posts (los tokens de acceso personal necesitan el scope indicado)
+        .route("/api/posts", get(post_handlers::get_feed).layer(Extension(RequiredScope(Scope::PostsRead))))
+        .route("/api/posts", post(post_handlers::create_post).layer(Extension(RequiredScope(Scope::PostsWrite))))
+        .route("/api/posts/:id/like", post(post_handlers::toggle_like).layer(Extension(RequiredScope(Scope::PostsWrite))))
+        .route("/api/posts/:id/pin", post(post_handlers::pin_post).layer(Extension(RequiredScope(Scope::PostsWrite))))
+        .route("/api/posts/:id/pin", delete(post_handlers::unpin_post).layer(Extension(RequiredScope(Scope::PostsWrite))))
         
         // Rutas de usuarios
+        .route("/api/users/search", get(user_handlers::search_users).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/autocomplete", get(user_handlers::autocomplete_users).layer(Extension(RequiredScope(Scope::ProfileRead))))
         .route("/api/users/:username", get(user_handlers::get_user_profile))
+        .route("/api/users/:username/follow", post(follow_handlers::follow_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/follow", delete(follow_handlers::unfollow_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/block", post(block_handlers::block_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/block", delete(block_handlers::unblock_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/mute", post(block_handlers::mute_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/mute", delete(block_handlers::unmute_user).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/:username/followers", get(follow_handlers::list_followers).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/:username/followers/mutual", get(follow_handlers::list_mutual_followers).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/:username/following", get(follow_handlers::list_following).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/:username/posts", get(post_handlers::list_user_posts).layer(Extension(RequiredScope(Scope::PostsRead))))
+        .route("/api/users/me", get(user_handlers::get_my_profile).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/me", patch(user_handlers::update_my_profile).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/me", delete(account_handlers::delete_account))
+        .route("/api/users/me/avatar", post(media_handlers::upload_avatar).layer(DefaultBodyLimit::max(media::max_upload_bytes())))
+        .route("/api/users/me/avatar", delete(media_handlers::delete_avatar))
+        .route("/api/users/me/banner", post(media_handlers::upload_banner).layer(DefaultBodyLimit::max(media::max_upload_bytes())))
+        .route("/api/users/me/banner", delete(media_handlers::delete_banner))
+        .route("/api/users/me/follow-requests", get(follow_handlers::list_follow_requests).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/me/follow-requests/:username", post(follow_handlers::approve_follow_request).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/me/follow-requests/:username", delete(follow_handlers::reject_follow_request).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/me/suggestions", get(suggestion_handlers::list_suggestions).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/me/suggestions/:username", delete(suggestion_handlers::dismiss_suggestion).layer(Extension(RequiredScope(Scope::ProfileWrite))))
+        .route("/api/users/me/blocks", get(block_handlers::list_blocked).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/me/mutes", get(block_handlers::list_muted).layer(Extension(RequiredScope(Scope::ProfileRead))))
+        .route("/api/users/me/verification", get(verified_account_handlers::verification_status))
+        .route("/api/users/me/verification", post(verified_account_handlers::request_verification))
+        .route("/api/users/me/verification", delete(verified_account_handlers::cancel_verification_request))
+        .route("/api/users/me/export", get(export_handlers::export_status))
+        .route("/api/users/me/export", post(export_handlers::request_export))
+        .route("/api/exports/:token", get(export_handlers::download_export))
+        .route("/api/users/me/password", post(account_handlers::change_password))
+        .route("/api/users/me/email", post(account_handlers::change_email))
+        .route("/api/users/me/username", post(account_handlers::change_username))
+        
+        // Rutas de administración
+        .route("/api/admin/users/:username/roles", get(admin_handlers::list_user_roles))
+        .route("/api/admin/users/:username/roles", post(admin_handlers::grant_role))
+        .route("/api/admin/users/:username/roles/:role", delete(admin_handlers::revoke_role))
+        .route("/api/admin/users/:username/ban", post(admin_handlers::ban_user))
+        .route("/api/admin/users/:username/ban", delete(admin_handlers::unban_user))
+        .route("/api/admin/lockouts", get(admin_handlers::list_lockouts))
+        .route("/api/admin/verification-requests", get(admin_handlers::list_verification_requests))
+        .route("/api/admin/verification-requests/:id/approve", post(admin_handlers::approve_verification))
+        .route("/api/admin/verification-requests/:id/reject", post(admin_handlers::reject_verification))
+        .route("/api/admin/users/:username/verification", delete(admin_handlers::revoke_verification))
+        .route("/api/admin/users/:username/verification/history", get(admin_handlers::verification_history))
         
-        // Estados compartidos
-        .with_state(user_repo)
-        .with_state(post_repo)
+        // Estado compartido
+        .with_state(state)
         
         // Middleware global
         .layer(
@@ -77,21 +201,103 @@ async fn main() {
                 .layer(CorsLayer::permissive())
         );
 
+    // Con almacenamiento local la propia API sirve los archivos subidos
+    let app = match storage::local_media_dir() {
+        Some(dir) => app.nest_service("/media", ServeDir::new(dir)),
+        None => app,
+    };
+
     // Configurar dirección del servidor
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
     println!("🚀 Servidor corriendo en http://localhost:8080");
     println!("📚 API Endpoints disponibles:");
     println!("   GET  /health");
+    println!("   GET  /.well-known/jwks.json");
+    println!("   GET  /media/* (archivos subidos, con almacenamiento local)");
     println!("   POST /api/auth/register");
     println!("   POST /api/auth/login");
+    println!("   POST /api/auth/refresh");
+    println!("   POST /api/auth/logout (requiere auth)");
+    println!("   POST /api/auth/logout/all (requiere auth)");
+    println!("   POST /api/auth/password/forgot");
+    println!("   POST /api/auth/password/reset");
+    println!("   POST /api/auth/email/verify");
+    println!("   POST /api/auth/email/resend (requiere auth)");
+    println!("   POST /api/auth/2fa/enroll (requiere auth)");
+    println!("   POST /api/auth/2fa/confirm (requiere auth)");
+    println!("   POST /api/auth/2fa/disable (requiere auth)");
+    println!("   POST /api/auth/2fa/verify");
+    println!("   GET  /api/auth/sessions (requiere auth)");
+    println!("   DELETE /api/auth/sessions/:id (requiere auth)");
+    println!("   GET  /api/auth/tokens (requiere auth)");
+    println!("   POST /api/auth/tokens (requiere auth)");
+    println!("   DELETE /api/auth/tokens/:id (requiere auth)");
+    println!("   POST /api/auth/magic-link");
+    println!("   POST /api/auth/magic-link/verify");
+    println!("   GET  /api/auth/oauth/providers");
+    println!("   POST /api/auth/oauth/signup");
+    println!("   POST /api/auth/oauth/:provider/authorize (con auth, vincula la cuenta)");
+    println!("   POST /api/auth/oauth/:provider/callback");
+    println!("   GET  /api/auth/identities (requiere auth)");
+    println!("   DELETE /api/auth/identities/:provider (requiere auth)");
     println!("   GET  /api/posts");
-    println!("   POST /api/posts (requiere auth)");
+    println!("   POST /api/posts (requiere auth y email verificado)");
     println!("   POST /api/posts/:id/like (requiere auth)");
+    println!("   POST /api/posts/:id/pin (requiere auth)");
+    println!("   DELETE /api/posts/:id/pin (requiere auth)");
+    println!("   GET  /api/users/search?q=");
+    println!("   GET  /api/users/autocomplete?q=");
     println!("   GET  /api/users/:username");
+    println!("   POST /api/users/:username/follow (requiere auth)");
+    println!("   DELETE /api/users/:username/follow (requiere auth)");
+    println!("   POST /api/users/:username/block (requiere auth)");
+    println!("   DELETE /api/users/:username/block (requiere auth)");
+    println!("   POST /api/users/:username/mute (requiere auth)");
+    println!("   DELETE /api/users/:username/mute (requiere auth)");
+    println!("   GET  /api/users/:username/followers");
+    println!("   GET  /api/users/:username/followers/mutual (requiere auth)");
+    println!("   GET  /api/users/:username/following");
+    println!("   GET  /api/users/:username/posts");
+    println!("   GET  /api/users/me (requiere auth)");
+    println!("   PATCH /api/users/me (requiere auth)");
+    println!("   DELETE /api/users/me (requiere auth)");
+    println!("   POST /api/users/me/avatar (requiere auth, multipart)");
+    println!("   DELETE /api/users/me/avatar (requiere auth)");
+    println!("   POST /api/users/me/banner (requiere auth, multipart)");
+    println!("   DELETE /api/users/me/banner (requiere auth)");
+    println!("   GET  /api/users/me/follow-requests (requiere auth)");
+    println!("   POST /api/users/me/follow-requests/:username (requiere auth, aprueba)");
+    println!("   DELETE /api/users/me/follow-requests/:username (requiere auth, rechaza)");
+    println!("   GET  /api/users/me/suggestions (requiere auth)");
+    println!("   DELETE /api/users/me/suggestions/:username (requiere auth)");
+    println!("   GET  /api/users/me/blocks (requiere auth)");
+    println!("   GET  /api/users/me/mutes (requiere auth)");
+    println!("   GET  /api/users/me/verification (requiere auth)");
+    println!("   POST /api/users/me/verification (requiere email verificado)");
+    println!("   DELETE /api/users/me/verification (requiere auth)");
+    println!("   GET  /api/users/me/export (requiere auth)");
+    println!("   POST /api/users/me/export (requiere auth)");
+    println!("   GET  /api/exports/:token");
+    println!("   POST /api/users/me/password (requiere auth)");
+    println!("   POST /api/users/me/email (requiere auth)");
+    println!("   POST /api/users/me/username (requiere auth)");
+    println!("   GET  /api/admin/users/:username/roles (requiere roles:manage)");
+    println!("   POST /api/admin/users/:username/roles (requiere roles:manage)");
+    println!("   DELETE /api/admin/users/:username/roles/:role (requiere roles:manage)");
+    println!("   POST /api/admin/users/:username/ban (requiere users:suspend)");
+    println!("   DELETE /api/admin/users/:username/ban (requiere users:suspend)");
+    println!("   GET  /api/admin/lockouts (requiere security:read)");
+    println!("   GET  /api/admin/verification-requests (requiere users:verify)");
+    println!("   POST /api/admin/verification-requests/:id/approve (requiere users:verify)");
+    println!("   POST /api/admin/verification-requests/:id/reject (requiere users:verify)");
+    println!("   DELETE /api/admin/users/:username/verification (requiere users:verify)");
+    println!("   GET  /api/admin/users/:username/verification/history (requiere users:verify)");
     
     // Iniciar servidor
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
 
 async fn health_check() -> Json<ApiResponse<serde_json::Value>> {
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use image::{
+    codecs::webp::WebPEncoder, imageops::FilterType, DynamicImage, ExtendedColorType, ImageDecoder,
+    ImageFormat, ImageReader, Limits,
+};
+use std::io::Cursor;
+use uuid::Uuid;
+
+use crate::storage::Storage;
+
+// Imágenes más grandes se rechazan antes de reservar memoria para decodificarlas
+const MAX_DIMENSION: u32 = 8000;
+const MAX_DECODE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Imágenes del perfil y los tamaños fijos (nombre, ancho, alto) que se generan de cada una.
+/// La primera variante es la que se guarda como URL del perfil.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileImage {
+    Avatar,
+    Banner,
+}
+
+impl ProfileImage {
+    pub fn variants(&self) -> &'static [(&'static str, u32, u32)] {
+        match self {
+            ProfileImage::Avatar => &[("400", 400, 400), ("200", 200, 200), ("64", 64, 64)],
+            ProfileImage::Banner => &[("1500x500", 1500, 500), ("600x200", 600, 200)],
+        }
+    }
+
+    fn folder(&self) -> &'static str {
+        match self {
+            ProfileImage::Avatar => "avatars",
+            ProfileImage::Banner => "banners",
+        }
+    }
+}
+
+pub struct ImageVariant {
+    pub name: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Imagen guardada: `key` es el prefijo común de sus variantes.
+pub struct StoredImage {
+    pub key: String,
+    pub url: String,
+    pub variants: Vec<(&'static str, String)>,
+}
+
+/// Tamaño máximo de una subida (`UPLOAD_MAX_BYTES`, 5 MB por defecto).
+pub fn max_upload_bytes() -> usize {
+    std::env::var("UPLOAD_MAX_BYTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(5 * 1024 * 1024)
+}
+
+/// Tipo real de la imagen según sus primeros bytes; el Content-Type del cliente no cuenta.
+pub fn sniff_format(data: &[u8]) -> Option<ImageFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageFormat::Jpeg)
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(ImageFormat::Png)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(ImageFormat::Gif)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(ImageFormat::WebP)
+    } else {
+        None
+    }
+}
+
+/// Decodifica la imagen, aplica la orientación EXIF y genera cada variante recortada al centro
+/// en WebP. Al recodificar desde los píxeles no se conserva ningún metadato (EXIF, GPS...).
+/// Es trabajo de CPU: llamarla desde `spawn_blocking`.
+pub fn process(kind: ProfileImage, data: &[u8], format: ImageFormat) -> Result<Vec<ImageVariant>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    kind.variants()
+        .iter()
+        .map(|&(name, width, height)| {
+            let resized = image.resize_to_fill(width, height, FilterType::Lanczos3).to_rgba8();
+
+            let mut data = Vec::new();
+            WebPEncoder::new_lossless(&mut data).encode(
+                resized.as_raw(),
+                resized.width(),
+                resized.height(),
+                ExtendedColorType::Rgba8,
+            )?;
+
+            Ok(ImageVariant { name, data })
+        })
+        .collect()
+}
+
+/// Sube las variantes bajo una clave nueva, de modo que las URLs anteriores no se queden en caché.
+pub async fn store(
+    storage: &dyn Storage,
+    kind: ProfileImage,
+    user_id: Uuid,
+    variants: Vec<ImageVariant>,
+) -> Result<StoredImage> {
+    let key = format!("{}/{}/{}", kind.folder(), user_id, Uuid::new_v4());
+    let mut urls = Vec::with_capacity(variants.len());
+
+    for variant in variants {
+        let variant_key = format!("{}/{}.webp", key, variant.name);
+        if let Err(e) = storage.put(&variant_key, variant.data).await {
+            remove(storage, kind, &key).await;
+            return Err(e);
+        }
+        urls.push((variant.name, storage.url(&variant_key)));
+    }
+
+    Ok(StoredImage {
+        url: urls[0].1.clone(),
+        key,
+        variants: urls,
+    })
+}
+
+/// Borra todas las variantes de una imagen; los fallos sólo se registran.
+pub async fn remove(storage: &dyn Storage, kind: ProfileImage, key: &str) {
+    for (name, _, _) in kind.variants() {
+        if let Err(e) = storage.delete(&format!("{}/{}.webp", key, name)).await {
+            tracing::warn!("No se pudo borrar {}/{}.webp: {}", key, name, e);
+        }
+    }
+}
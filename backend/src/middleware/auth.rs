@@ -1,24 +1,37 @@
 use axum::{
     async_trait,
-    extract::{FromRequestParts, State},
+    extract::{FromRef, FromRequestParts},
     http::{request::Parts, StatusCode, HeaderMap},
-    response::{Json, IntoResponse},
+    response::Json,
 };
+use chrono::Utc;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::auth::JwtService;
-use crate::repository::UserRepository;
+use crate::auth::rbac::{self, Permission, Role};
+use crate::auth::scopes::RequiredScope;
+use crate::auth::{hash_token, AccountStateCache, JwtService, ACCESS_TOKEN_PREFIX};
+use crate::middleware::ClientInfo;
 use crate::models::ApiResponse;
 
 pub struct AuthUser {
     pub id: Uuid,
     pub username: String,
+    pub session_id: Option<Uuid>, // None si se autenticó con un token de acceso personal
+    pub roles: Vec<Role>,
+}
+
+impl AuthUser {
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        rbac::has_permission(&self.roles, permission)
+    }
 }
 
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
+    Arc<AccountStateCache>: FromRef<S>,
+    Arc<JwtService>: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = (StatusCode, Json<ApiResponse<()>>);
@@ -33,7 +46,12 @@ where
                 )
             })?;
 
-        let jwt_service = JwtService::new();
+        let account_cache = Arc::<AccountStateCache>::from_ref(state);
+        if token.starts_with(ACCESS_TOKEN_PREFIX) {
+            return from_access_token(parts, state, &account_cache, &token).await;
+        }
+
+        let jwt_service = Arc::<JwtService>::from_ref(state);
         let claims = jwt_service.verify_token(&token)
             .map_err(|_| {
                 (
@@ -50,13 +68,132 @@ where
                 )
             })?;
 
+        let session_id = claims.sid.parse::<Uuid>()
+            .map_err(|_| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error("Token malformado"))
+                )
+            })?;
+
+        // Rechazar tokens de sesiones revocadas, cuentas desactivadas o suspendidas,
+        // y tokens emitidos antes de un cambio de contraseña o de roles
+        let account = match account_cache.get(user_id, session_id).await {
+            Ok(Some(account)) => account,
+            Ok(None) => return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("Sesión revocada"))
+            )),
+            Err(_) => return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error("Error del servidor"))
+            ))
+        };
+
+        if !account.session_active {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("Sesión revocada"))
+            ));
+        }
+
+        if !account.is_active {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("Cuenta desactivada"))
+            ));
+        }
+
+        if account.is_banned {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::error("Cuenta suspendida"))
+            ));
+        }
+
+        if claims.ver != account.token_version {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("Token caducado, renueva tu sesión"))
+            ));
+        }
+
         Ok(AuthUser {
             id: user_id,
             username: claims.username,
+            session_id: Some(session_id),
+            roles: claims.roles,
         })
     }
 }
 
+// Autentica con un token de acceso personal; sólo vale en rutas que declaran un scope
+async fn from_access_token<S: Send + Sync>(
+    parts: &mut Parts,
+    state: &S,
+    account_cache: &AccountStateCache,
+    token: &str,
+) -> Result<AuthUser, (StatusCode, Json<ApiResponse<()>>)> {
+    let Some(RequiredScope(required_scope)) = parts.extensions.get::<RequiredScope>().copied() else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Este endpoint no admite tokens de acceso personal"))
+        ));
+    };
+
+    let client = match ClientInfo::from_request_parts(parts, state).await {
+        Ok(client) => client,
+        Err(never) => match never {},
+    };
+
+    let access_token = match account_cache.get_access_token(&hash_token(token), client.ip_address.as_deref()).await {
+        Ok(Some(access_token)) => access_token,
+        Ok(None) => return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Token inválido"))
+        )),
+        Err(_) => return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error("Error del servidor"))
+        ))
+    };
+
+    if !access_token.token_active || access_token.expires_at <= Utc::now() {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Token de acceso revocado o expirado"))
+        ));
+    }
+
+    if !access_token.is_active {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::error("Cuenta desactivada"))
+        ));
+    }
+
+    if access_token.is_banned {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Cuenta suspendida"))
+        ));
+    }
+
+    if !access_token.scopes.iter().any(|scope| scope == required_scope.as_str()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error(&format!("El token no tiene el scope requerido: {}", required_scope.as_str())))
+        ));
+    }
+
+    Ok(AuthUser {
+        id: access_token.user_id,
+        username: access_token.username,
+        session_id: None,
+        roles: vec![Role::User],
+    })
+}
+
 fn extract_token_from_header(headers: &HeaderMap) -> Option<String> {
     let header = headers.get("Authorization")?;
     let auth_header = header.to_str().ok()?;
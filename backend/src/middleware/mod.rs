@@ -1,3 +1,9 @@
 pub mod auth;
+pub mod client;
+pub mod permission;
+pub mod verified;
 
 pub use auth::AuthUser;
+pub use client::ClientInfo;
+pub use permission::RequirePermission;
+pub use verified::VerifiedUser;
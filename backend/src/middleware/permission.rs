@@ -0,0 +1,46 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{request::Parts, StatusCode},
+    response::Json,
+};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::auth::rbac::RequiredPermission;
+use crate::auth::{AccountStateCache, JwtService};
+use crate::middleware::AuthUser;
+use crate::models::ApiResponse;
+
+/// Usuario autenticado con el permiso `P`; si no lo tiene, la petición se rechaza con 403.
+pub struct RequirePermission<P> {
+    pub user: AuthUser,
+    _permission: PhantomData<fn() -> P>,
+}
+
+#[async_trait]
+impl<S, P> FromRequestParts<S> for RequirePermission<P>
+where
+    P: RequiredPermission,
+    Arc<AccountStateCache>: FromRef<S>,
+    Arc<JwtService>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<ApiResponse<()>>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+
+        if !user.has_permission(P::PERMISSION) {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::error("No tienes permisos para realizar esta acción"))
+            ));
+        }
+
+        Ok(RequirePermission {
+            user,
+            _permission: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{request::Parts, StatusCode},
+    response::Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::{AccountStateCache, JwtService};
+use crate::middleware::AuthUser;
+use crate::repository::UserRepository;
+use crate::models::ApiResponse;
+
+/// Usuario autenticado que además cumple la política de verificación de email.
+/// Se usa en las acciones restringidas a cuentas verificadas (publicar, mensajes directos).
+pub struct VerifiedUser {
+    pub id: Uuid,
+    pub username: String,
+    pub session_id: Option<Uuid>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for VerifiedUser
+where
+    Arc<AccountStateCache>: FromRef<S>,
+    Arc<JwtService>: FromRef<S>,
+    Arc<UserRepository>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<ApiResponse<()>>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        if require_verified_email() {
+            let user_repo = Arc::<UserRepository>::from_ref(state);
+            match user_repo.find_by_id(auth_user.id).await {
+                Ok(Some(user)) if user.email_verified_at.is_some() => {},
+                Ok(Some(_)) => return Err((
+                    StatusCode::FORBIDDEN,
+                    Json(ApiResponse::error("Debes verificar tu email para realizar esta acción"))
+                )),
+                Ok(None) => return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::error("Usuario no encontrado"))
+                )),
+                Err(_) => return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error("Error del servidor"))
+                ))
+            }
+        }
+
+        Ok(VerifiedUser {
+            id: auth_user.id,
+            username: auth_user.username,
+            session_id: auth_user.session_id,
+        })
+    }
+}
+
+// Política configurable: REQUIRE_VERIFIED_EMAIL=false permite actuar a cuentas sin verificar
+fn require_verified_email() -> bool {
+    std::env::var("REQUIRE_VERIFIED_EMAIL")
+        .map(|value| value != "false" && value != "0")
+        .unwrap_or(true)
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// Vista pública de un token; el secreto nunca se vuelve a mostrar
+#[derive(Debug, Serialize)]
+pub struct AccessTokenInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PersonalAccessToken> for AccessTokenInfo {
+    fn from(token: PersonalAccessToken) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            token_prefix: token.token_prefix,
+            scopes: token.scopes,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            last_used_ip: token.last_used_ip,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateAccessTokenRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<String>,
+    #[validate(range(min = 1, max = 365))]
+    pub expires_in_days: Option<i64>,
+}
+
+// Respuesta de creación: única vez que se devuelve el token en claro
+#[derive(Debug, Serialize)]
+pub struct CreatedAccessToken {
+    pub token: String,
+    pub access_token: AccessTokenInfo,
+}
+
+/// Datos que `AuthUser` necesita para autenticar una petición con un token personal.
+#[derive(Debug, Clone)]
+pub struct AccessTokenState {
+    pub token_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub scopes: Vec<String>,
+    pub token_active: bool,
+    pub expires_at: DateTime<Utc>, // se vuelve a comprobar en cada petición, aunque venga de la caché
+    pub is_active: bool,
+    pub is_banned: bool,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use validator::Validate;
+
+#[derive(Debug, Serialize)]
+pub struct BlockedUserEntry {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub blocked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MutedUserEntry {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub muted_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MuteRequest {
+    #[validate(range(min = 1, max = 8760))]
+    pub duration_hours: Option<i64>, // sin duración, el silencio es indefinido
+}
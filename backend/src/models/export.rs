@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub file_path: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub token_hash: Option<String>,
+    pub error: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+// Vista para el usuario; la ruta del archivo y el token no se exponen
+#[derive(Debug, Serialize)]
+pub struct DataExportInfo {
+    pub id: Uuid,
+    pub status: String,
+    pub size_bytes: Option<i64>,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<DataExport> for DataExportInfo {
+    fn from(export: DataExport) -> Self {
+        Self {
+            id: export.id,
+            status: export.status,
+            size_bytes: export.size_bytes,
+            requested_at: export.requested_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+        }
+    }
+}
+
+// Archivos del usuario que se copian a `media/` en el paquete
+#[derive(Debug)]
+pub struct ExportMedia {
+    pub avatar_key: Option<String>,
+    pub banner_key: Option<String>,
+    pub post_images: Vec<(Uuid, String)>, // (post, image_url)
+}
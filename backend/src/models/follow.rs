@@ -0,0 +1,36 @@
+use serde::Serialize;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// Usuario en una lista de seguidores o seguidos. Los indicadores de relación con quien
+/// consulta son `null` sin sesión.
+#[derive(Debug, Serialize)]
+pub struct FollowListEntry {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_verified: bool,
+    pub followed_at: DateTime<Utc>,
+    pub you_follow: Option<bool>,
+    pub follows_you: Option<bool>,
+}
+
+/// Relación entre quien consulta y otro usuario.
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct FollowRelationship {
+    pub you_follow: bool,
+    pub follows_you: bool,
+    pub follow_requested: bool,
+}
+
+/// Solicitud de seguimiento recibida por una cuenta privada.
+#[derive(Debug, Serialize)]
+pub struct FollowRequestEntry {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_verified: bool,
+    pub requested_at: DateTime<Utc>,
+}
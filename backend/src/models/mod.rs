@@ -1,10 +1,32 @@
 pub mod user;
 pub mod post;
 pub mod chat;
+pub mod access_token;
+pub mod block;
+pub mod export;
+pub mod follow;
+pub mod oauth;
+pub mod role;
+pub mod security;
+pub mod suggestion;
+pub mod verification;
+pub mod token;
+pub mod two_factor;
 
 pub use user::*;
 pub use post::*;
 pub use chat::*;
+pub use access_token::*;
+pub use block::*;
+pub use export::*;
+pub use follow::*;
+pub use oauth::*;
+pub use role::*;
+pub use security::*;
+pub use suggestion::*;
+pub use verification::*;
+pub use token::*;
+pub use two_factor::*;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -40,5 +62,7 @@ impl<T> ApiResponse<T> {
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64, // segundos de vida del access token
     pub user: UserProfile,
 }
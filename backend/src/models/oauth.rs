@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use validator::Validate;
+
+use super::{validate_username, AuthResponse, TwoFactorPending};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OAuthState {
+    pub id: Uuid,
+    pub state_hash: String,
+    pub provider: String,
+    pub code_verifier: String,
+    pub link_user_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub link_nonce_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OAuthSignup {
+    pub id: Uuid,
+    pub token_hash: String,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub display_name: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OAuthAuthorization {
+    pub authorization_url: String,
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_nonce: Option<String>, // sólo al vincular; el callback debe devolverlo
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackRequest {
+    pub code: String,
+    pub state: String,
+    pub link_nonce: Option<String>,
+}
+
+// Primer login con el proveedor: falta elegir nombre de usuario
+#[derive(Debug, Serialize)]
+pub struct OAuthSignupPending {
+    pub signup_required: bool,
+    pub signup_token: String,
+    pub suggested_username: Option<String>,
+    pub email: Option<String>,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum OAuthCallbackResponse {
+    Authenticated(AuthResponse),
+    TwoFactorRequired(TwoFactorPending),
+    SignupRequired(OAuthSignupPending),
+    Linked(UserIdentity),
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OAuthSignupRequest {
+    pub signup_token: String,
+    #[validate(length(min = 3, max = 30), custom(function = "validate_username"))]
+    pub username: String,
+    #[validate(email)]
+    pub email: Option<String>, // obligatorio si el proveedor no lo facilitó
+}
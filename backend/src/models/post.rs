@@ -14,6 +14,7 @@ pub struct Post {
     pub comments_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub pinned_at: Option<DateTime<Utc>>, // fijado en el perfil del autor
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +30,14 @@ pub struct PostWithUser {
     pub comments_count: i32,
     pub created_at: DateTime<Utc>,
     pub is_liked: Option<bool>,
+    pub is_pinned: bool,
+}
+
+/// Posts de un perfil: los fijados (sólo en la primera página) y después la cronología.
+#[derive(Debug, Serialize)]
+pub struct ProfilePosts {
+    pub pinned: Vec<PostWithUser>,
+    pub posts: Vec<PostWithUser>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct RoleAssignment {
+    pub user_id: Uuid,
+    pub role: String, // "moderator" o "admin"
+    pub granted_by: Option<Uuid>,
+    pub granted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRoles {
+    pub user_id: Uuid,
+    pub username: String,
+    pub roles: Vec<RoleAssignment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantRoleRequest {
+    pub role: String,
+}
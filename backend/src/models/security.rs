@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct LoginLockout {
+    pub id: Uuid,
+    pub scope: String, // "account" o "ip"
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub ip_address: Option<String>,
+    pub failures: i32,
+    pub locked_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,15 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Cuenta sugerida para seguir, con los motivos de la sugerencia.
+#[derive(Debug, Serialize)]
+pub struct SuggestedUser {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_verified: bool,
+    pub followers_count: i32,
+    pub mutual_count: i32,
+    pub shared_likes_count: i32,
+}
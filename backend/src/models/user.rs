@@ -1,9 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use sqlx::FromRow;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use validator::Validate;
 
+use super::FollowRelationship;
+
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -20,6 +23,49 @@ pub struct User {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub token_version: i32,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub banned_at: Option<DateTime<Utc>>,
+    pub banned_until: Option<DateTime<Utc>>,
+    pub ban_reason: Option<String>,
+    pub username_changed_at: Option<DateTime<Utc>>,
+    pub deletion_requested_at: Option<DateTime<Utc>>,
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    pub pronouns: Option<String>,
+    pub birthday: Option<NaiveDate>,
+    pub birthday_visibility: String,
+    pub avatar_key: Option<String>,
+    pub banner_url: Option<String>,
+    pub banner_key: Option<String>,
+    pub is_private: bool,
+    pub verification_badge: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    // Suspendida indefinidamente (sin banned_until) o hasta una fecha futura
+    pub fn is_banned(&self) -> bool {
+        self.banned_at.is_some() && self.banned_until.map_or(true, |until| until > Utc::now())
+    }
+}
+
+/// Estado mínimo de la cuenta que `AuthUser` comprueba en cada petición.
+#[derive(Debug, Clone, Copy)]
+pub struct AccountState {
+    pub session_active: bool,
+    pub is_active: bool,
+    pub is_banned: bool,
+    pub token_version: i32,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BanUserRequest {
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+    pub until: Option<DateTime<Utc>>, // sin fecha, la suspensión es indefinida
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -29,16 +75,134 @@ pub struct UserProfile {
     pub display_name: Option<String>,
     pub bio: Option<String>,
     pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub followers_count: i32,
+    pub following_count: i32,
+    pub posts_count: i32,
+    pub is_verified: bool,
+    pub verification_badge: Option<String>, // "person", "organization" o "government"
+    pub is_private: bool,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    pub pronouns: Option<String>,
+    pub birthday: Option<String>, // según birthday_visibility: "1990-05-17", "--05-17" o ausente
+    pub created_at: DateTime<Utc>,
+    pub you_follow: Option<bool>, // null sin sesión o en el perfil propio
+    pub follows_you: Option<bool>,
+    pub follow_requested: Option<bool>, // solicitud pendiente a una cuenta privada
+}
+
+impl UserProfile {
+    pub fn with_relationship(mut self, relationship: FollowRelationship) -> Self {
+        self.you_follow = Some(relationship.you_follow);
+        self.follows_you = Some(relationship.follows_you);
+        self.follow_requested = Some(relationship.follow_requested);
+        self
+    }
+}
+
+/// Perfil propio (`GET /api/users/me`): incluye los datos que no se muestran a otros usuarios.
+#[derive(Debug, Serialize)]
+pub struct PrivateUserProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    pub pronouns: Option<String>,
+    pub birthday: Option<NaiveDate>,
+    pub birthday_visibility: String,
+    pub is_private: bool,
     pub followers_count: i32,
     pub following_count: i32,
     pub posts_count: i32,
     pub is_verified: bool,
+    pub verification_badge: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BirthdayVisibility {
+    Public,
+    MonthDay,
+    Private,
+}
+
+impl BirthdayVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BirthdayVisibility::Public => "public",
+            BirthdayVisibility::MonthDay => "month_day",
+            BirthdayVisibility::Private => "private",
+        }
+    }
+}
+
+/// Cambios del perfil: un campo ausente no se toca y un `null` (o texto vacío) lo borra.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(max = 100))]
+    pub display_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(max = 300))]
+    pub bio: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(max = 200), url)]
+    pub website: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(max = 100))]
+    pub location: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(length(max = 40))]
+    pub pronouns: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub birthday: Option<Option<NaiveDate>>,
+    pub birthday_visibility: Option<BirthdayVisibility>,
+    pub is_private: Option<bool>,
+}
+
+// Distingue un campo ausente (None) de un `null` explícito (Some(None))
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Resultado de la búsqueda y el autocompletado de usuarios. La relación con quien busca es
+/// `null` sin sesión.
+#[derive(Debug, Serialize)]
+pub struct UserSearchResult {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub is_verified: bool,
+    pub is_private: bool,
+    pub followers_count: i32,
+    pub you_follow: Option<bool>,
+    pub follows_you: Option<bool>,
+}
+
+/// URLs de las variantes generadas al subir un avatar o banner.
+#[derive(Debug, Serialize)]
+pub struct UploadedImage {
+    pub url: String,
+    pub variants: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateUser {
-    #[validate(length(min = 3, max = 30))]
+    #[validate(length(min = 3, max = 30), custom(function = "validate_username"))]
     pub username: String,
     #[validate(email)]
     pub email: String,
@@ -48,6 +212,56 @@ pub struct CreateUser {
     pub display_name: Option<String>,
 }
 
+/// Sólo letras ASCII, dígitos y `_`: el nombre va en las rutas (`/api/users/:username`) y en
+/// las menciones.
+pub fn validate_username(username: &str) -> Result<(), validator::ValidationError> {
+    if username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("username_chars"))
+    }
+}
+
+// Nombres que no puede tomar ninguna cuenta nueva ni renombrada
+const RESERVED_USERNAMES: &[&str] = &[
+    "admin", "administrator", "api", "auth", "autocomplete", "help", "me", "moderator", "pitaia",
+    "root", "search", "security", "settings", "support", "system", "www",
+];
+
+pub fn is_reserved_username(username: &str) -> bool {
+    RESERVED_USERNAMES.contains(&username.to_lowercase().as_str())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    #[validate(length(min = 8))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangeEmailRequest {
+    pub current_password: String,
+    #[validate(email)]
+    pub new_email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangeUsernameRequest {
+    #[validate(length(min = 3, max = 30), custom(function = "validate_username"))]
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub current_password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletion {
+    pub deletion_scheduled_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct LoginUser {
     pub username: String,
@@ -62,11 +276,60 @@ impl From<User> for UserProfile {
             display_name: user.display_name,
             bio: user.bio,
             avatar_url: user.avatar_url,
+            banner_url: user.banner_url,
             followers_count: user.followers_count,
             following_count: user.following_count,
             posts_count: user.posts_count,
             is_verified: user.is_verified,
+            verification_badge: user.verification_badge,
+            is_private: user.is_private,
+            website: user.website,
+            location: user.location,
+            pronouns: user.pronouns,
+            birthday: visible_birthday(user.birthday, &user.birthday_visibility),
             created_at: user.created_at,
+            you_follow: None,
+            follows_you: None,
+            follow_requested: None,
         }
     }
 }
+
+impl From<User> for PrivateUserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            email_verified: user.email_verified_at.is_some(),
+            display_name: user.display_name,
+            bio: user.bio,
+            avatar_url: user.avatar_url,
+            banner_url: user.banner_url,
+            website: user.website,
+            location: user.location,
+            pronouns: user.pronouns,
+            birthday: user.birthday,
+            birthday_visibility: user.birthday_visibility,
+            is_private: user.is_private,
+            followers_count: user.followers_count,
+            following_count: user.following_count,
+            posts_count: user.posts_count,
+            is_verified: user.is_verified,
+            verification_badge: user.verification_badge,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+// El año se omite con "month_day" (formato ISO 8601 sin año)
+fn visible_birthday(birthday: Option<NaiveDate>, visibility: &str) -> Option<String> {
+    let birthday = birthday?;
+
+    match visibility {
+        "public" => Some(birthday.format("%Y-%m-%d").to_string()),
+        "month_day" => Some(birthday.format("--%m-%d").to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeType {
+    Person,
+    Organization,
+    Government,
+}
+
+impl BadgeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BadgeType::Person => "person",
+            BadgeType::Organization => "organization",
+            BadgeType::Government => "government",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, FromRow)]
+pub struct VerificationRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub badge_type: String, // "person", "organization" o "government"
+    pub legal_name: String,
+    pub details: String,
+    pub links: Vec<String>,
+    pub status: String, // "pending", "approved", "rejected" o "cancelled"
+    pub reviewed_by: Option<Uuid>,
+    pub review_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// Solicitud en la cola de revisión, con los datos de la cuenta que la envía.
+#[derive(Debug, Serialize)]
+pub struct VerificationQueueEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub followers_count: i32,
+    pub email_verified: bool,
+    pub badge_type: String,
+    pub legal_name: String,
+    pub details: String,
+    pub links: Vec<String>,
+    pub status: String,
+    pub reviewed_by: Option<Uuid>,
+    pub review_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct VerificationChange {
+    pub id: Uuid,
+    pub action: String, // "grant" o "revoke"
+    pub badge_type: String,
+    pub request_id: Option<Uuid>,
+    pub reason: Option<String>,
+    pub changed_by: Option<Uuid>, // null si la retiró el sistema (p. ej. cambio de nombre)
+    pub changed_by_username: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateVerificationRequest {
+    pub badge_type: BadgeType,
+    #[validate(length(min = 2, max = 200))]
+    pub legal_name: String,
+    #[validate(length(min = 20, max = 2000))]
+    pub details: String,
+    #[serde(default)]
+    #[validate(length(max = 5), custom(function = "validate_links"))]
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReviewVerificationRequest {
+    #[validate(length(max = 500))]
+    pub reason: Option<String>, // obligatorio al rechazar
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RevokeVerificationRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+fn validate_links(links: &[String]) -> Result<(), validator::ValidationError> {
+    let valid = links.iter().all(|link| {
+        link.len() <= 300
+            && url::Url::parse(link).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("links"))
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use url::Url;
+
+use crate::handlers::app_url;
+
+/// Cómo leer la identidad de la respuesta de userinfo de cada proveedor.
+#[derive(Debug, Clone)]
+pub struct ClaimMapping {
+    pub subject: &'static str,
+    pub email: &'static str,
+    pub email_verified: Option<&'static str>,
+    pub username: &'static str,
+    pub name: &'static str,
+}
+
+impl ClaimMapping {
+    // Claims estándar de OpenID Connect
+    pub const OIDC: ClaimMapping = ClaimMapping {
+        subject: "sub",
+        email: "email",
+        email_verified: Some("email_verified"),
+        username: "preferred_username",
+        name: "name",
+    };
+
+    // API de GitHub: /user no es OIDC y no indica si el email está verificado
+    pub const GITHUB: ClaimMapping = ClaimMapping {
+        subject: "id",
+        email: "email",
+        email_verified: None,
+        username: "login",
+        name: "name",
+    };
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthProvider {
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub auth_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    pub scopes: String,
+    pub redirect_uri: String,
+    pub claims: ClaimMapping,
+}
+
+/// Identidad devuelta por el proveedor tras el login.
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub username: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Cliente OAuth2 / OpenID Connect (authorization code + PKCE) para login social.
+///
+/// Configuración por entorno:
+/// - `OAUTH_PROVIDERS`: lista de proveedores separados por comas (`google,github,mock`)
+/// - `OAUTH_<P>_CLIENT_ID`, `OAUTH_<P>_CLIENT_SECRET`
+/// - `OAUTH_<P>_ISSUER`: emisor OIDC; los endpoints se obtienen por discovery
+/// - `OAUTH_<P>_AUTH_URL`, `OAUTH_<P>_TOKEN_URL`, `OAUTH_<P>_USERINFO_URL`: sustituyen al discovery
+/// - `OAUTH_<P>_SCOPES` (por defecto `openid email profile`)
+/// - `OAUTH_<P>_REDIRECT_URI` (por defecto `APP_URL/auth/callback/<p>`)
+///
+/// `google` y `github` traen sus endpoints preconfigurados; basta con el client id y secret.
+pub struct OAuthClient {
+    http: reqwest::Client,
+    providers: HashMap<String, OAuthProvider>,
+}
+
+impl OAuthClient {
+    pub async fn from_env() -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent("pitaia-api")
+            .timeout(std::time::Duration::from_secs(10))
+            .build()?;
+
+        let names = std::env::var("OAUTH_PROVIDERS").unwrap_or_default();
+        let mut providers = HashMap::new();
+
+        for name in names.split(',').map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()) {
+            let provider = load_provider(&http, &name).await?;
+            tracing::info!("Proveedor OAuth configurado: {}", name);
+            providers.insert(name, provider);
+        }
+
+        Ok(Self { http, providers })
+    }
+
+    pub fn provider(&self, name: &str) -> Option<&OAuthProvider> {
+        self.providers.get(name)
+    }
+
+    pub fn provider_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.providers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// URL a la que se redirige al usuario para autorizar el acceso.
+    pub fn authorization_url(&self, provider: &OAuthProvider, state: &str, code_verifier: &str) -> Result<String> {
+        let mut url = Url::parse(&provider.auth_url)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &provider.redirect_uri)
+            .append_pair("scope", &provider.scopes)
+            .append_pair("state", state)
+            .append_pair("code_challenge", &code_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Canjea el código de autorización y devuelve la identidad del usuario en el proveedor.
+    pub async fn fetch_identity(&self, provider: &OAuthProvider, code: &str, code_verifier: &str) -> Result<ExternalIdentity> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = provider.client_secret.as_deref() {
+            form.push(("client_secret", secret));
+        }
+
+        let token: TokenResponse = self.http
+            .post(&provider.token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let userinfo: Value = self.http
+            .get(&provider.userinfo_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let claims = &provider.claims;
+        let subject = claim_string(&userinfo, claims.subject)
+            .ok_or_else(|| anyhow::anyhow!("La respuesta de {} no incluye {}", provider.name, claims.subject))?;
+        let email_verified = claims.email_verified
+            .and_then(|field| userinfo.get(field))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        Ok(ExternalIdentity {
+            subject,
+            email: claim_string(&userinfo, claims.email),
+            email_verified,
+            username: claim_string(&userinfo, claims.username),
+            display_name: claim_string(&userinfo, claims.name),
+        })
+    }
+}
+
+async fn load_provider(http: &reqwest::Client, name: &str) -> Result<OAuthProvider> {
+    let prefix = format!("OAUTH_{}_", name.to_uppercase());
+    let var = |key: &str| std::env::var(format!("{}{}", prefix, key)).ok();
+
+    let client_id = var("CLIENT_ID")
+        .ok_or_else(|| anyhow::anyhow!("Falta {}CLIENT_ID", prefix))?;
+
+    let (issuer, claims, defaults) = match name {
+        "google" => (Some("https://accounts.google.com".to_string()), ClaimMapping::OIDC, None),
+        "github" => (None, ClaimMapping::GITHUB, Some((
+            "https://github.com/login/oauth/authorize",
+            "https://github.com/login/oauth/access_token",
+            "https://api.github.com/user",
+        ))),
+        _ => (None, ClaimMapping::OIDC, None),
+    };
+    let issuer = var("ISSUER").or(issuer);
+
+    // Los endpoints explícitos tienen prioridad sobre el discovery del emisor
+    let (mut auth_url, mut token_url, mut userinfo_url) = (var("AUTH_URL"), var("TOKEN_URL"), var("USERINFO_URL"));
+    if let Some((auth, token, userinfo)) = defaults {
+        auth_url = auth_url.or(Some(auth.to_string()));
+        token_url = token_url.or(Some(token.to_string()));
+        userinfo_url = userinfo_url.or(Some(userinfo.to_string()));
+    }
+
+    if auth_url.is_none() || token_url.is_none() || userinfo_url.is_none() {
+        let Some(issuer) = issuer else {
+            return Err(anyhow::anyhow!("Configura {}ISSUER o los endpoints del proveedor {}", prefix, name));
+        };
+        let discovery_url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let document: DiscoveryDocument = http.get(&discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Discovery de {} fallido: {}", name, e))?;
+
+        auth_url = auth_url.or(Some(document.authorization_endpoint));
+        token_url = token_url.or(Some(document.token_endpoint));
+        userinfo_url = userinfo_url.or(Some(document.userinfo_endpoint));
+    }
+
+    let default_scopes = if name == "github" { "read:user user:email" } else { "openid email profile" };
+
+    Ok(OAuthProvider {
+        name: name.to_string(),
+        client_id,
+        client_secret: var("CLIENT_SECRET"),
+        auth_url: auth_url.unwrap_or_default(),
+        token_url: token_url.unwrap_or_default(),
+        userinfo_url: userinfo_url.unwrap_or_default(),
+        scopes: var("SCOPES").unwrap_or_else(|| default_scopes.to_string()),
+        redirect_uri: var("REDIRECT_URI").unwrap_or_else(|| format!("{}/auth/callback/{}", app_url(), name)),
+        claims,
+    })
+}
+
+// Los identificadores pueden llegar como texto o como número (GitHub)
+fn claim_string(userinfo: &Value, field: &str) -> Option<String> {
+    match userinfo.get(field)? {
+        Value::String(value) if !value.is_empty() => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Verificador PKCE: 32 bytes aleatorios en base64url (43 caracteres).
+pub fn generate_code_verifier() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{AccessTokenState, PersonalAccessToken};
+
+pub struct AccessTokenRepository {
+    pool: PgPool,
+}
+
+impl AccessTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        name: &str,
+        token_hash: &str,
+        token_prefix: &str,
+        scopes: &[String],
+        expires_at: DateTime<Utc>,
+    ) -> Result<PersonalAccessToken> {
+        let token = sqlx::query_as!(
+            PersonalAccessToken,
+            r#"
+            INSERT INTO personal_access_tokens (user_id, name, token_hash, token_prefix, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            user_id,
+            name,
+            token_hash,
+            token_prefix,
+            scopes,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn list_active(&self, user_id: Uuid) -> Result<Vec<PersonalAccessToken>> {
+        let tokens = sqlx::query_as!(
+            PersonalAccessToken,
+            r#"
+            SELECT * FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn count_active(&self, user_id: Uuid) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM personal_access_tokens
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn revoke(&self, token_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE personal_access_tokens SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            token_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Estado del token y de su dueño para autenticar una petición.
+    pub async fn find_state(&self, token_hash: &str) -> Result<Option<AccessTokenState>> {
+        let state = sqlx::query_as!(
+            AccessTokenState,
+            r#"
+            SELECT t.id as token_id, t.user_id, u.username, t.scopes,
+                   (t.revoked_at IS NULL AND t.expires_at > NOW()) as "token_active!",
+                   t.expires_at,
+                   u.is_active as "is_active!",
+                   (u.banned_at IS NOT NULL AND (u.banned_until IS NULL OR u.banned_until > NOW())) as "is_banned!"
+            FROM personal_access_tokens t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.token_hash = $1
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    /// Registra el último uso (como mucho una vez por minuto).
+    pub async fn touch(&self, token_id: Uuid, ip_address: Option<&str>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE personal_access_tokens SET last_used_at = NOW(), last_used_ip = $2
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+            "#,
+            token_id,
+            ip_address
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
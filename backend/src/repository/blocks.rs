@@ -0,0 +1,157 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{BlockedUserEntry, MutedUserEntry};
+use super::follows::delete_follow;
+
+/// Bloqueos y silenciados entre usuarios.
+pub struct BlockRepository {
+    pool: PgPool,
+}
+
+impl BlockRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Bloquea a `blocked_id` y rompe cualquier relación de seguimiento entre ambos.
+    /// Devuelve `false` si ya estaba bloqueado.
+    pub async fn block(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO user_blocks (blocker_id, blocked_id)
+            VALUES ($1, $2)
+            ON CONFLICT (blocker_id, blocked_id) DO NOTHING
+            "#,
+            blocker_id,
+            blocked_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() == 1;
+
+        delete_follow(&mut tx, blocker_id, blocked_id).await?;
+        delete_follow(&mut tx, blocked_id, blocker_id).await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM follow_requests
+            WHERE (requester_id = $1 AND target_id = $2) OR (requester_id = $2 AND target_id = $1)
+            "#,
+            blocker_id,
+            blocked_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    pub async fn unblock(&self, blocker_id: Uuid, blocked_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2",
+            blocker_id,
+            blocked_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Si alguno de los dos ha bloqueado al otro.
+    pub async fn is_blocked_between(&self, user_id: Uuid, other_id: Uuid) -> Result<bool> {
+        let blocked = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM user_blocks
+                WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+            ) as "blocked!"
+            "#,
+            user_id,
+            other_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(blocked)
+    }
+
+    pub async fn blocked_users(&self, blocker_id: Uuid, limit: i64, offset: i64) -> Result<Vec<BlockedUserEntry>> {
+        let entries = sqlx::query_as!(
+            BlockedUserEntry,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.avatar_url, b.created_at as blocked_at
+            FROM user_blocks b
+            JOIN users u ON u.id = b.blocked_id
+            WHERE b.blocker_id = $1
+            ORDER BY b.created_at DESC, u.id
+            LIMIT $2 OFFSET $3
+            "#,
+            blocker_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Silencia (o cambia la duración del silencio) a `muted_id`; sin `expires_at` es indefinido.
+    pub async fn mute(&self, muter_id: Uuid, muted_id: Uuid, expires_at: Option<DateTime<Utc>>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_mutes (muter_id, muted_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (muter_id, muted_id)
+            DO UPDATE SET expires_at = EXCLUDED.expires_at, created_at = NOW()
+            "#,
+            muter_id,
+            muted_id,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unmute(&self, muter_id: Uuid, muted_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM user_mutes WHERE muter_id = $1 AND muted_id = $2",
+            muter_id,
+            muted_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Silencios vigentes; los caducados dejan de aplicarse aunque sigan en la tabla.
+    pub async fn muted_users(&self, muter_id: Uuid, limit: i64, offset: i64) -> Result<Vec<MutedUserEntry>> {
+        let entries = sqlx::query_as!(
+            MutedUserEntry,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.avatar_url, m.created_at as muted_at, m.expires_at
+            FROM user_mutes m
+            JOIN users u ON u.id = m.muted_id
+            WHERE m.muter_id = $1 AND (m.expires_at IS NULL OR m.expires_at > NOW())
+            ORDER BY m.created_at DESC, u.id
+            LIMIT $2 OFFSET $3
+            "#,
+            muter_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::EmailVerificationToken;
+
+pub struct EmailVerificationRepository {
+    pool: PgPool,
+}
+
+impl EmailVerificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Crea un token nuevo para `email` e invalida los anteriores que no se hayan usado.
+    /// Con `is_change`, `email` es la dirección nueva y se aplica al confirmar el token.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+        is_change: bool,
+    ) -> Result<EmailVerificationToken> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE email_verification_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let token = sqlx::query_as!(
+            EmailVerificationToken,
+            r#"
+            INSERT INTO email_verification_tokens (user_id, email, token_hash, expires_at, is_change)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+            user_id,
+            email,
+            token_hash,
+            expires_at,
+            is_change
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    pub async fn last_sent_at(&self, user_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        let sent_at = sqlx::query_scalar!(
+            "SELECT MAX(created_at) FROM email_verification_tokens WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(sent_at)
+    }
+
+    /// Consume el token y marca el email como verificado, siempre que siga siendo el email de la cuenta.
+    /// Si es un cambio de email, sustituye el email de la cuenta por el nuevo, ya verificado.
+    /// Devuelve el id del usuario verificado o `None` si el token no es válido.
+    pub async fn verify(&self, token_hash: &str) -> Result<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+
+        let token = sqlx::query!(
+            r#"
+            UPDATE email_verification_tokens SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id, email, is_change
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(token) = token else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        let result = if token.is_change {
+            // La dirección pudo registrarse en otra cuenta mientras el cambio estaba pendiente
+            sqlx::query!(
+                r#"
+                UPDATE users SET email = $2, email_verified_at = NOW(), updated_at = NOW()
+                WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM users WHERE LOWER(email) = LOWER($2))
+                "#,
+                token.user_id,
+                token.email
+            )
+            .execute(&mut *tx)
+            .await?
+        } else {
+            sqlx::query!(
+                r#"
+                UPDATE users SET email_verified_at = NOW(), updated_at = NOW()
+                WHERE id = $1 AND email = $2
+                "#,
+                token.user_id,
+                token.email
+            )
+            .execute(&mut *tx)
+            .await?
+        };
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        tx.commit().await?;
+        Ok(Some(token.user_id))
+    }
+}
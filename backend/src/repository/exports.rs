@@ -0,0 +1,355 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{DataExport, ExportMedia};
+
+pub struct ExportRepository {
+    pool: PgPool,
+}
+
+impl ExportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, user_id: Uuid) -> Result<DataExport> {
+        let export = sqlx::query_as!(
+            DataExport,
+            "INSERT INTO data_exports (user_id) VALUES ($1) RETURNING *",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    pub async fn latest_for_user(&self, user_id: Uuid) -> Result<Option<DataExport>> {
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            SELECT * FROM data_exports
+            WHERE user_id = $1
+            ORDER BY requested_at DESC
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Toma la exportación pendiente más antigua; varias instancias no procesan la misma.
+    pub async fn claim_next(&self) -> Result<Option<DataExport>> {
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            UPDATE data_exports SET status = 'processing', started_at = NOW()
+            WHERE id = (
+                SELECT id FROM data_exports
+                WHERE status = 'pending'
+                ORDER BY requested_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Devuelve a la cola las exportaciones que quedaron a medias (p. ej. por un reinicio)
+    /// y que ningún worker ha tocado desde `started_before`.
+    pub async fn requeue_stale(&self, started_before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE data_exports SET status = 'pending'
+            WHERE status = 'processing' AND started_at < $1
+            "#,
+            started_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// `false` si la exportación ya no existe (la cuenta se purgó mientras se generaba).
+    pub async fn mark_ready(
+        &self,
+        id: Uuid,
+        file_path: &str,
+        size_bytes: i64,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = 'ready', file_path = $2, size_bytes = $3, token_hash = $4,
+                completed_at = NOW(), expires_at = $5
+            WHERE id = $1
+            "#,
+            id,
+            file_path,
+            size_bytes,
+            token_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn mark_failed(&self, id: Uuid, error: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE data_exports SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1",
+            id,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_downloadable(&self, token_hash: &str) -> Result<Option<DataExport>> {
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            SELECT * FROM data_exports
+            WHERE token_hash = $1 AND status = 'ready' AND expires_at > NOW()
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Marca como caducadas las exportaciones vencidas y las devuelve para borrar sus archivos.
+    pub async fn expire_due(&self) -> Result<Vec<DataExport>> {
+        let exports = sqlx::query_as!(
+            DataExport,
+            r#"
+            UPDATE data_exports SET status = 'expired', token_hash = NULL
+            WHERE status = 'ready' AND expires_at <= NOW()
+            RETURNING *
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    /// Datos personales del usuario, una sección por archivo del paquete.
+    pub async fn personal_data(&self, user_id: Uuid) -> Result<Vec<(&'static str, Value)>> {
+        let profile = sqlx::query_scalar!(
+            r#"
+            SELECT to_jsonb(u) - 'password_hash' AS "data!"
+            FROM users u
+            WHERE u.id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let posts = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(to_jsonb(p) ORDER BY p.created_at), '[]'::jsonb) AS "data!"
+            FROM posts p
+            WHERE p.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let likes = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'post_id', l.post_id,
+                'author', u.username,
+                'content', p.content,
+                'created_at', l.created_at
+            ) ORDER BY l.created_at), '[]'::jsonb) AS "data!"
+            FROM likes l
+            JOIN posts p ON p.id = l.post_id
+            JOIN users u ON u.id = p.user_id
+            WHERE l.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let followers = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'username', u.username,
+                'created_at', f.created_at
+            ) ORDER BY f.created_at), '[]'::jsonb) AS "data!"
+            FROM follows f
+            JOIN users u ON u.id = f.follower_id
+            WHERE f.following_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let following = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'username', u.username,
+                'created_at', f.created_at
+            ) ORDER BY f.created_at), '[]'::jsonb) AS "data!"
+            FROM follows f
+            JOIN users u ON u.id = f.following_id
+            WHERE f.follower_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let sessions = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'user_agent', s.user_agent,
+                'ip_address', s.ip_address,
+                'created_at', s.created_at,
+                'last_seen_at', s.last_seen_at,
+                'revoked_at', s.revoked_at
+            ) ORDER BY s.created_at), '[]'::jsonb) AS "data!"
+            FROM sessions s
+            WHERE s.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let identities = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'provider', i.provider,
+                'email', i.email,
+                'created_at', i.created_at,
+                'last_login_at', i.last_login_at
+            ) ORDER BY i.created_at), '[]'::jsonb) AS "data!"
+            FROM user_identities i
+            WHERE i.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let blocked = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'username', u.username,
+                'created_at', b.created_at
+            ) ORDER BY b.created_at), '[]'::jsonb) AS "data!"
+            FROM user_blocks b
+            JOIN users u ON u.id = b.blocked_id
+            WHERE b.blocker_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let muted = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'username', u.username,
+                'created_at', m.created_at,
+                'expires_at', m.expires_at
+            ) ORDER BY m.created_at), '[]'::jsonb) AS "data!"
+            FROM user_mutes m
+            JOIN users u ON u.id = m.muted_id
+            WHERE m.muter_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let verification_requests = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                'badge_type', r.badge_type,
+                'legal_name', r.legal_name,
+                'details', r.details,
+                'links', r.links,
+                'status', r.status,
+                'review_reason', r.review_reason,
+                'created_at', r.created_at,
+                'reviewed_at', r.reviewed_at
+            ) ORDER BY r.created_at), '[]'::jsonb) AS "data!"
+            FROM verification_requests r
+            WHERE r.user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(vec![
+            ("profile", profile),
+            ("posts", posts),
+            ("likes", likes),
+            ("followers", followers),
+            ("following", following),
+            ("sessions", sessions),
+            ("linked_accounts", identities),
+            ("blocked_accounts", blocked),
+            ("muted_accounts", muted),
+            ("verification_requests", verification_requests),
+        ])
+    }
+
+    /// Imágenes del usuario: claves del avatar y la portada, y las URLs de las imágenes de sus posts.
+    pub async fn media(&self, user_id: Uuid) -> Result<ExportMedia> {
+        let profile = sqlx::query!(
+            "SELECT avatar_key, banner_key FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let post_images = sqlx::query!(
+            r#"
+            SELECT id, image_url AS "image_url!"
+            FROM posts
+            WHERE user_id = $1 AND image_url IS NOT NULL
+            ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.image_url))
+        .collect();
+
+        Ok(ExportMedia {
+            avatar_key: profile.avatar_key,
+            banner_key: profile.banner_key,
+            post_images,
+        })
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::models::{FollowListEntry, FollowRelationship, FollowRequestEntry, User};
+
+pub struct FollowRepository {
+    pool: PgPool,
+}
+
+impl FollowRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Crea la relación y actualiza ambos contadores; `false` si ya se seguían.
+    pub async fn follow(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let inserted = insert_follow(&mut tx, follower_id, following_id).await?;
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Borra la relación y descuenta ambos contadores; `false` si no se seguían.
+    pub async fn unfollow(&self, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let deleted = delete_follow(&mut tx, follower_id, following_id).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    pub async fn relationship(&self, viewer_id: Uuid, user_id: Uuid) -> Result<FollowRelationship> {
+        let relationship = sqlx::query_as!(
+            FollowRelationship,
+            r#"
+            SELECT
+                EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = $2) as "you_follow!",
+                EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = $1) as "follows_you!",
+                EXISTS(SELECT 1 FROM follow_requests WHERE requester_id = $1 AND target_id = $2) as "follow_requested!"
+            "#,
+            viewer_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(relationship)
+    }
+
+    /// Si `viewer_id` puede ver los posts y listas de `owner`: siempre en cuentas públicas,
+    /// y en las privadas sólo el dueño y sus seguidores.
+    pub async fn can_view(&self, viewer_id: Option<Uuid>, owner: &User) -> Result<bool> {
+        if !owner.is_private || viewer_id == Some(owner.id) {
+            return Ok(true);
+        }
+
+        let Some(viewer_id) = viewer_id else {
+            return Ok(false);
+        };
+
+        let following = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = $2) as "following!""#,
+            viewer_id,
+            owner.id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(following)
+    }
+
+    /// Registra una solicitud a una cuenta privada; `false` si ya estaba pendiente.
+    pub async fn request_follow(&self, requester_id: Uuid, target_id: Uuid) -> Result<bool> {
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO follow_requests (requester_id, target_id)
+            VALUES ($1, $2)
+            ON CONFLICT (requester_id, target_id) DO NOTHING
+            "#,
+            requester_id,
+            target_id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected() == 1;
+
+        Ok(inserted)
+    }
+
+    /// Retira (quien la hizo) o rechaza (la cuenta privada) una solicitud pendiente.
+    pub async fn delete_request(&self, requester_id: Uuid, target_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM follow_requests WHERE requester_id = $1 AND target_id = $2",
+            requester_id,
+            target_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Convierte la solicitud en seguimiento; `false` si no había solicitud pendiente.
+    pub async fn approve_request(&self, requester_id: Uuid, target_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let pending = sqlx::query!(
+            "DELETE FROM follow_requests WHERE requester_id = $1 AND target_id = $2",
+            requester_id,
+            target_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() > 0;
+
+        if pending {
+            insert_follow(&mut tx, requester_id, target_id).await?;
+        }
+
+        tx.commit().await?;
+        Ok(pending)
+    }
+
+    /// Aprueba todas las solicitudes pendientes, al pasar la cuenta a pública.
+    pub async fn approve_all_requests(&self, target_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            WITH moved AS (
+                DELETE FROM follow_requests WHERE target_id = $1 RETURNING requester_id
+            ), inserted AS (
+                INSERT INTO follows (follower_id, following_id)
+                SELECT requester_id, $1 FROM moved
+                ON CONFLICT (follower_id, following_id) DO NOTHING
+                RETURNING follower_id
+            ), requesters AS (
+                UPDATE users SET following_count = following_count + 1
+                WHERE id IN (SELECT follower_id FROM inserted)
+            )
+            UPDATE users SET followers_count = followers_count + (SELECT COUNT(*) FROM inserted)
+            WHERE id = $1
+            "#,
+            target_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn pending_requests(&self, target_id: Uuid, limit: i64, offset: i64) -> Result<Vec<FollowRequestEntry>> {
+        let entries = sqlx::query_as!(
+            FollowRequestEntry,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                r.created_at as requested_at
+            FROM follow_requests r
+            JOIN users u ON u.id = r.requester_id
+            WHERE r.target_id = $1 AND u.is_active = true
+            ORDER BY r.created_at DESC, u.id
+            LIMIT $2 OFFSET $3
+            "#,
+            target_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Quienes siguen a `user_id`, del más reciente al más antiguo.
+    pub async fn followers(
+        &self,
+        user_id: Uuid,
+        viewer_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FollowListEntry>> {
+        let entries = sqlx::query_as!(
+            FollowListEntry,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                f.created_at as "followed_at!",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = u.id)
+                END as "you_follow: bool",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $2)
+                END as "follows_you: bool"
+            FROM follows f
+            JOIN users u ON u.id = f.follower_id
+            WHERE f.following_id = $1 AND u.is_active = true
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $2 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $2)
+              )
+            ORDER BY f.created_at DESC, u.id
+            LIMIT $3 OFFSET $4
+            "#,
+            user_id,
+            viewer_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// A quienes sigue `user_id`, del más reciente al más antiguo.
+    pub async fn following(
+        &self,
+        user_id: Uuid,
+        viewer_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FollowListEntry>> {
+        let entries = sqlx::query_as!(
+            FollowListEntry,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                f.created_at as "followed_at!",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = u.id)
+                END as "you_follow: bool",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $2)
+                END as "follows_you: bool"
+            FROM follows f
+            JOIN users u ON u.id = f.following_id
+            WHERE f.follower_id = $1 AND u.is_active = true
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $2 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $2)
+              )
+            ORDER BY f.created_at DESC, u.id
+            LIMIT $3 OFFSET $4
+            "#,
+            user_id,
+            viewer_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Seguidores de `user_id` a los que también sigue `viewer_id` ("le siguen personas que conoces").
+    pub async fn mutual_followers(
+        &self,
+        viewer_id: Uuid,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FollowListEntry>> {
+        let entries = sqlx::query_as!(
+            FollowListEntry,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                f.created_at as "followed_at!",
+                true as "you_follow: bool",
+                EXISTS(SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $1) as "follows_you: bool"
+            FROM follows f
+            JOIN follows mine ON mine.following_id = f.follower_id AND mine.follower_id = $1
+            JOIN users u ON u.id = f.follower_id
+            WHERE f.following_id = $2 AND u.is_active = true
+            ORDER BY f.created_at DESC, u.id
+            LIMIT $3 OFFSET $4
+            "#,
+            viewer_id,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
+
+// Inserta el seguimiento y suma ambos contadores dentro de la transacción dada
+async fn insert_follow(tx: &mut Transaction<'_, Postgres>, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO follows (follower_id, following_id)
+        VALUES ($1, $2)
+        ON CONFLICT (follower_id, following_id) DO NOTHING
+        "#,
+        follower_id,
+        following_id
+    )
+    .execute(&mut **tx)
+    .await?
+    .rows_affected() == 1;
+
+    if inserted {
+        sqlx::query!(
+            "UPDATE users SET following_count = following_count + 1 WHERE id = $1",
+            follower_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE users SET followers_count = followers_count + 1 WHERE id = $1",
+            following_id
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(inserted)
+}
+
+// Borra el seguimiento y descuenta ambos contadores dentro de la transacción dada
+pub(super) async fn delete_follow(tx: &mut Transaction<'_, Postgres>, follower_id: Uuid, following_id: Uuid) -> Result<bool> {
+    let deleted = sqlx::query!(
+        "DELETE FROM follows WHERE follower_id = $1 AND following_id = $2",
+        follower_id,
+        following_id
+    )
+    .execute(&mut **tx)
+    .await?
+    .rows_affected() == 1;
+
+    if deleted {
+        sqlx::query!(
+            "UPDATE users SET following_count = GREATEST(following_count - 1, 0) WHERE id = $1",
+            follower_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE users SET followers_count = GREATEST(followers_count - 1, 0) WHERE id = $1",
+            following_id
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(deleted)
+}
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{OAuthSignup, OAuthState, User, UserIdentity};
+
+pub struct IdentityRepository {
+    pool: PgPool,
+}
+
+impl IdentityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_state(
+        &self,
+        state_hash: &str,
+        provider: &str,
+        code_verifier: &str,
+        link_user_id: Option<Uuid>,
+        link_nonce_hash: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO oauth_states (state_hash, provider, code_verifier, link_user_id, link_nonce_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            state_hash,
+            provider,
+            code_verifier,
+            link_user_id,
+            link_nonce_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Marca el state como usado; sólo vale una vez y antes de caducar
+    pub async fn consume_state(&self, state_hash: &str, provider: &str) -> Result<Option<OAuthState>> {
+        let state = sqlx::query_as!(
+            OAuthState,
+            r#"
+            UPDATE oauth_states SET used_at = NOW()
+            WHERE state_hash = $1 AND provider = $2 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING *
+            "#,
+            state_hash,
+            provider
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    // Cuenta vinculada a la identidad; registra el login
+    pub async fn find_user(&self, provider: &str, subject: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            WITH identity AS (
+                UPDATE user_identities SET last_login_at = NOW()
+                WHERE provider = $1 AND subject = $2
+                RETURNING user_id
+            )
+            SELECT users.id, users.username, users.email, users.password_hash, users.display_name,
+                   users.bio, users.avatar_url, users.followers_count as "followers_count!",
+                   users.following_count as "following_count!", users.posts_count as "posts_count!",
+                   users.is_verified as "is_verified!", users.is_active as "is_active!",
+                   users.created_at as "created_at!", users.updated_at as "updated_at!",
+                   users.email_verified_at, users.token_version, users.password_changed_at,
+                   users.banned_at, users.banned_until, users.ban_reason, users.username_changed_at,
+                   users.deletion_requested_at, users.deletion_scheduled_at, users.website,
+                   users.location, users.pronouns, users.birthday, users.birthday_visibility,
+                   users.avatar_key, users.banner_url, users.banner_key, users.is_private,
+                   users.verification_badge, users.verified_at
+            FROM users
+            JOIN identity ON identity.user_id = users.id
+            WHERE users.is_active = true OR users.deletion_scheduled_at > NOW()
+            "#,
+            provider,
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn is_linked(&self, provider: &str, subject: &str) -> Result<bool> {
+        let linked = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM user_identities WHERE provider = $1 AND subject = $2) AS "linked!""#,
+            provider,
+            subject
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(linked)
+    }
+
+    // Devuelve None si la identidad ya estaba vinculada a alguna cuenta
+    pub async fn link(&self, user_id: Uuid, provider: &str, subject: &str, email: Option<&str>) -> Result<Option<UserIdentity>> {
+        let identity = sqlx::query_as!(
+            UserIdentity,
+            r#"
+            INSERT INTO user_identities (user_id, provider, subject, email)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, subject) DO NOTHING
+            RETURNING *
+            "#,
+            user_id,
+            provider,
+            subject,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(identity)
+    }
+
+    pub async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<UserIdentity>> {
+        let identities = sqlx::query_as!(
+            UserIdentity,
+            "SELECT * FROM user_identities WHERE user_id = $1 ORDER BY created_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(identities)
+    }
+
+    pub async fn unlink(&self, user_id: Uuid, provider: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM user_identities WHERE user_id = $1 AND provider = $2",
+            user_id,
+            provider
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_signup(
+        &self,
+        token_hash: &str,
+        provider: &str,
+        subject: &str,
+        email: Option<&str>,
+        email_verified: bool,
+        display_name: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO oauth_signups (token_hash, provider, subject, email, email_verified, display_name, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            token_hash,
+            provider,
+            subject,
+            email,
+            email_verified,
+            display_name,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_signup(&self, token_hash: &str) -> Result<Option<OAuthSignup>> {
+        let signup = sqlx::query_as!(
+            OAuthSignup,
+            r#"
+            SELECT * FROM oauth_signups
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(signup)
+    }
+
+    /// Crea la cuenta y la identidad de un registro pendiente en una sola transacción.
+    /// Devuelve None si el registro ya se completó mientras tanto.
+    pub async fn complete_signup(
+        &self,
+        signup: &OAuthSignup,
+        username: &str,
+        email: &str,
+        password_hash: &str,
+    ) -> Result<Option<User>> {
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query!(
+            "UPDATE oauth_signups SET used_at = NOW() WHERE id = $1 AND used_at IS NULL",
+            signup.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        // El email sólo se da por verificado si es el que confirmó el proveedor
+        let email_verified = signup.email_verified && signup.email.as_deref() == Some(email);
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (username, email, password_hash, display_name, email_verified_at)
+            VALUES ($1, $2, $3, $4, CASE WHEN $5 THEN NOW() END)
+            RETURNING id, username, email, password_hash, display_name, bio, avatar_url,
+                      followers_count as "followers_count!", following_count as "following_count!",
+                      posts_count as "posts_count!", is_verified as "is_verified!",
+                      is_active as "is_active!", created_at as "created_at!",
+                      updated_at as "updated_at!", email_verified_at, token_version,
+                      password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                      deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                      birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                      verification_badge, verified_at
+            "#,
+            username,
+            email,
+            password_hash,
+            signup.display_name,
+            email_verified
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_identities (user_id, provider, subject, email, last_login_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            "#,
+            user.id,
+            signup.provider,
+            signup.subject,
+            signup.email
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(user))
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::LoginLockout;
+
+pub struct LoginLockoutRepository {
+    pool: PgPool,
+}
+
+impl LoginLockoutRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(
+        &self,
+        scope: &str,
+        user_id: Option<Uuid>,
+        username: Option<&str>,
+        ip_address: Option<&str>,
+        failures: i32,
+        locked_until: DateTime<Utc>,
+    ) -> Result<LoginLockout> {
+        let lockout = sqlx::query_as!(
+            LoginLockout,
+            r#"
+            INSERT INTO login_lockouts (scope, user_id, username, ip_address, failures, locked_until)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            scope,
+            user_id,
+            username,
+            ip_address,
+            failures,
+            locked_until
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(lockout)
+    }
+
+    pub async fn list_recent(&self, limit: i64, offset: i64) -> Result<Vec<LoginLockout>> {
+        let lockouts = sqlx::query_as!(
+            LoginLockout,
+            "SELECT * FROM login_lockouts ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lockouts)
+    }
+}
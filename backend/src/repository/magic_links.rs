@@ -0,0 +1,75 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::MagicLink;
+
+pub struct MagicLinkRepository {
+    pool: PgPool,
+}
+
+impl MagicLinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Option<Uuid>,
+        email: &str,
+        token_hash: &str,
+        nonce_hash: &str,
+        ip_address: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<MagicLink> {
+        let link = sqlx::query_as!(
+            MagicLink,
+            r#"
+            INSERT INTO magic_links (user_id, email, token_hash, nonce_hash, ip_address, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            user_id,
+            email,
+            token_hash,
+            nonce_hash,
+            ip_address,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    /// Enlaces pedidos para un email desde `since`, existan o no como cuenta.
+    pub async fn count_since(&self, email: &str, since: DateTime<Utc>) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM magic_links WHERE email = $1 AND created_at > $2"#,
+            email,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Consume el enlace si el nonce coincide; devuelve el usuario, o `None` si no es válido.
+    pub async fn consume(&self, token_hash: &str, nonce_hash: &str) -> Result<Option<Uuid>> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            UPDATE magic_links SET used_at = NOW()
+            WHERE token_hash = $1 AND nonce_hash = $2 AND user_id IS NOT NULL
+              AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id AS "user_id!"
+            "#,
+            token_hash,
+            nonce_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
+}
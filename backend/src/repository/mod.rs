@@ -1,5 +1,35 @@
 pub mod users;
 pub mod posts;
+pub mod refresh_tokens;
+pub mod sessions;
+pub mod password_resets;
+pub mod email_verifications;
+pub mod two_factor;
+pub mod login_lockouts;
+pub mod roles;
+pub mod access_tokens;
+pub mod blocks;
+pub mod exports;
+pub mod follows;
+pub mod identities;
+pub mod magic_links;
+pub mod suggestions;
+pub mod verifications;
 
 pub use users::UserRepository;
 pub use posts::PostRepository;
+pub use refresh_tokens::RefreshTokenRepository;
+pub use sessions::SessionRepository;
+pub use password_resets::PasswordResetRepository;
+pub use email_verifications::EmailVerificationRepository;
+pub use two_factor::TwoFactorRepository;
+pub use login_lockouts::LoginLockoutRepository;
+pub use roles::{RoleRepository, RoleRevocation};
+pub use access_tokens::AccessTokenRepository;
+pub use blocks::BlockRepository;
+pub use exports::ExportRepository;
+pub use follows::FollowRepository;
+pub use identities::IdentityRepository;
+pub use magic_links::MagicLinkRepository;
+pub use suggestions::SuggestionRepository;
+pub use verifications::VerificationRepository;
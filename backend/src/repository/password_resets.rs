@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::PasswordResetToken;
+
+pub struct PasswordResetRepository {
+    pool: PgPool,
+}
+
+impl PasswordResetRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Crea un token nuevo e invalida los anteriores que no se hayan usado.
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PasswordResetToken> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE password_reset_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let token = sqlx::query_as!(
+            PasswordResetToken,
+            r#"
+            INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    /// Consume el token y cambia la contraseña; cierra además todas las sesiones del usuario.
+    /// Devuelve el usuario afectado, o `None` si el token no existe, ya se usó o expiró.
+    pub async fn reset_password(&self, token_hash: &str, password_hash: &str) -> Result<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+
+        let token = sqlx::query!(
+            r#"
+            UPDATE password_reset_tokens SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(token) = token else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET password_hash = $2, password_changed_at = NOW(),
+                token_version = token_version + 1, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            token.user_id,
+            password_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            token.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            token.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Una recuperación de cuenta también invalida los tokens personales que pudo crear un intruso
+        sqlx::query!(
+            "UPDATE personal_access_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            token.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(token.user_id))
+    }
+}
@@ -21,7 +21,9 @@ impl PostRepository {
             r#"
             INSERT INTO posts (user_id, content, image_url)
             VALUES ($1, $2, $3)
-            RETURNING *
+            RETURNING id, user_id, content, image_url, likes_count as "likes_count!",
+                      comments_count as "comments_count!", created_at as "created_at!",
+                      updated_at as "updated_at!", pinned_at
             "#,
             user_id,
             data.content,
@@ -55,13 +57,28 @@ impl PostRepository {
                     u.avatar_url,
                     p.content,
                     p.image_url,
-                    p.likes_count,
-                    p.comments_count,
-                    p.created_at,
-                    CASE WHEN l.user_id IS NOT NULL THEN true ELSE false END as "is_liked: bool"
+                    p.likes_count as "likes_count!",
+                    p.comments_count as "comments_count!",
+                    p.created_at as "created_at!",
+                    CASE WHEN l.user_id IS NOT NULL THEN true ELSE false END as "is_liked: bool",
+                    (p.pinned_at IS NOT NULL) as "is_pinned!"
                 FROM posts p
                 JOIN users u ON p.user_id = u.id
                 LEFT JOIN likes l ON p.id = l.post_id AND l.user_id = $1
+                WHERE u.is_active = true
+                  AND (u.is_private = false OR u.id = $1 OR EXISTS(
+                      SELECT 1 FROM follows f WHERE f.follower_id = $1 AND f.following_id = u.id
+                  ))
+                  AND NOT EXISTS(
+                      SELECT 1 FROM user_blocks b
+                      WHERE (b.blocker_id = $1 AND b.blocked_id = u.id)
+                         OR (b.blocker_id = u.id AND b.blocked_id = $1)
+                  )
+                  AND NOT EXISTS(
+                      SELECT 1 FROM user_mutes m
+                      WHERE m.muter_id = $1 AND m.muted_id = u.id
+                        AND (m.expires_at IS NULL OR m.expires_at > NOW())
+                  )
                 ORDER BY p.created_at DESC
                 LIMIT $2 OFFSET $3
                 "#,
@@ -83,12 +100,14 @@ impl PostRepository {
                     u.avatar_url,
                     p.content,
                     p.image_url,
-                    p.likes_count,
-                    p.comments_count,
-                    p.created_at,
-                    NULL as "is_liked: Option<bool>"
+                    p.likes_count as "likes_count!",
+                    p.comments_count as "comments_count!",
+                    p.created_at as "created_at!",
+                    NULL::boolean as "is_liked",
+                    (p.pinned_at IS NOT NULL) as "is_pinned!"
                 FROM posts p
                 JOIN users u ON p.user_id = u.id
+                WHERE u.is_active = true AND u.is_private = false
                 ORDER BY p.created_at DESC
                 LIMIT $1 OFFSET $2
                 "#,
@@ -102,6 +121,187 @@ impl PostRepository {
         Ok(posts)
     }
 
+    /// Si el post existe y `viewer_id` puede verlo: autor activo, sin bloqueos entre ambos y,
+    /// si su cuenta es privada, seguido por quien mira.
+    pub async fn is_visible(&self, post_id: Uuid, viewer_id: Option<Uuid>) -> Result<bool> {
+        let visible = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1
+                FROM posts p
+                JOIN users u ON p.user_id = u.id
+                WHERE p.id = $1
+                  AND u.is_active = true
+                  AND (u.is_private = false OR u.id = $2 OR EXISTS(
+                      SELECT 1 FROM follows f WHERE f.follower_id = $2 AND f.following_id = u.id
+                  ))
+                  AND NOT EXISTS(
+                      SELECT 1 FROM user_blocks b
+                      WHERE (b.blocker_id = $2 AND b.blocked_id = u.id)
+                         OR (b.blocker_id = u.id AND b.blocked_id = $2)
+                  )
+            ) as "visible!"
+            "#,
+            post_id,
+            viewer_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(visible)
+    }
+
+    pub async fn find_by_id(&self, post_id: Uuid) -> Result<Option<Post>> {
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT id, user_id, content, image_url, likes_count as "likes_count!",
+                   comments_count as "comments_count!", created_at as "created_at!",
+                   updated_at as "updated_at!", pinned_at
+            FROM posts
+            WHERE id = $1
+            "#,
+            post_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(post)
+    }
+
+    /// Posts fijados del autor, del más reciente al más antiguo. La visibilidad del perfil
+    /// la comprueba quien llama.
+    pub async fn pinned_posts(&self, author_id: Uuid, viewer_id: Option<Uuid>) -> Result<Vec<PostWithUser>> {
+        let posts = sqlx::query_as!(
+            PostWithUser,
+            r#"
+            SELECT
+                p.id,
+                p.user_id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                p.content,
+                p.image_url,
+                p.likes_count as "likes_count!",
+                p.comments_count as "comments_count!",
+                p.created_at as "created_at!",
+                CASE WHEN $2::uuid IS NULL THEN NULL ELSE EXISTS(
+                    SELECT 1 FROM likes l WHERE l.post_id = p.id AND l.user_id = $2
+                ) END as "is_liked",
+                true as "is_pinned!"
+            FROM posts p
+            JOIN users u ON p.user_id = u.id
+            WHERE p.user_id = $1 AND p.pinned_at IS NOT NULL
+            ORDER BY p.pinned_at DESC
+            "#,
+            author_id,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(posts)
+    }
+
+    /// Cronología del autor sin los posts fijados, que se muestran aparte.
+    pub async fn user_timeline(
+        &self,
+        author_id: Uuid,
+        viewer_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<PostWithUser>> {
+        let posts = sqlx::query_as!(
+            PostWithUser,
+            r#"
+            SELECT
+                p.id,
+                p.user_id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                p.content,
+                p.image_url,
+                p.likes_count as "likes_count!",
+                p.comments_count as "comments_count!",
+                p.created_at as "created_at!",
+                CASE WHEN $2::uuid IS NULL THEN NULL ELSE EXISTS(
+                    SELECT 1 FROM likes l WHERE l.post_id = p.id AND l.user_id = $2
+                ) END as "is_liked",
+                false as "is_pinned!"
+            FROM posts p
+            JOIN users u ON p.user_id = u.id
+            WHERE p.user_id = $1 AND p.pinned_at IS NULL
+            ORDER BY p.created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            author_id,
+            viewer_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(posts)
+    }
+
+    /// Fija el post en el perfil de su autor; `false` si ya tiene `max_pinned` posts fijados.
+    pub async fn pin(&self, user_id: Uuid, post_id: Uuid, max_pinned: i64) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        // Bloquear al autor serializa las fijaciones simultáneas y respeta el límite
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let pinned = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM posts
+            WHERE user_id = $1 AND pinned_at IS NOT NULL AND id <> $2
+            "#,
+            user_id,
+            post_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if pinned >= max_pinned {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE posts SET pinned_at = COALESCE(pinned_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            "#,
+            post_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Quita el post de los fijados; `false` si no estaba fijado.
+    pub async fn unpin(&self, user_id: Uuid, post_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE posts SET pinned_at = NULL
+            WHERE id = $1 AND user_id = $2 AND pinned_at IS NOT NULL
+            "#,
+            post_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn toggle_like(&self, user_id: Uuid, post_id: Uuid) -> Result<bool> {
         let mut tx = self.pool.begin().await?;
         
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::RefreshToken;
+
+pub struct RefreshTokenRepository {
+    pool: PgPool,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<RefreshToken> {
+        let token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            INSERT INTO refresh_tokens (user_id, session_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+            user_id,
+            session_id,
+            token_hash,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let token = sqlx::query_as!(
+            RefreshToken,
+            "SELECT * FROM refresh_tokens WHERE token_hash = $1",
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Sustituye `current` por un token nuevo de la misma sesión y extiende la sesión.
+    /// Devuelve `None` si `current` ya había sido usado (rotación concurrente o reutilización).
+    pub async fn rotate(
+        &self,
+        current: &RefreshToken,
+        new_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Option<RefreshToken>> {
+        let mut tx = self.pool.begin().await?;
+
+        let new_token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            INSERT INTO refresh_tokens (user_id, session_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+            current.user_id,
+            current.session_id,
+            new_hash,
+            expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Marcar el token actual como consumido sólo si nadie lo usó antes
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW(), replaced_by = $2
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            current.id,
+            new_token.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        sqlx::query!(
+            "UPDATE sessions SET expires_at = $2, last_seen_at = NOW() WHERE id = $1",
+            current.session_id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(new_token))
+    }
+}
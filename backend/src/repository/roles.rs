@@ -0,0 +1,133 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::auth::rbac::Role;
+use crate::models::RoleAssignment;
+
+/// Resultado de `RoleRepository::revoke`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoleRevocation {
+    Revoked,
+    NotAssigned,
+    LastAdmin, // era el único administrador
+}
+
+pub struct RoleRepository {
+    pool: PgPool,
+}
+
+impl RoleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Roles efectivos del usuario; el rol básico "user" siempre está incluido
+    pub async fn roles_for_user(&self, user_id: Uuid) -> Result<Vec<Role>> {
+        let assigned = sqlx::query_scalar!(
+            "SELECT role FROM user_roles WHERE user_id = $1 ORDER BY role",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut roles = vec![Role::User];
+        roles.extend(assigned.iter().filter_map(|role| Role::parse(role)));
+        Ok(roles)
+    }
+
+    pub async fn list_assignments(&self, user_id: Uuid) -> Result<Vec<RoleAssignment>> {
+        let assignments = sqlx::query_as!(
+            RoleAssignment,
+            "SELECT * FROM user_roles WHERE user_id = $1 ORDER BY granted_at",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assignments)
+    }
+
+    // Devuelve false si el usuario ya tenía el rol
+    pub async fn grant(&self, user_id: Uuid, role: Role, granted_by: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_roles (user_id, role, granted_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, role) DO NOTHING
+            "#,
+            user_id,
+            role.as_str(),
+            granted_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "INSERT INTO role_changes (user_id, role, action, changed_by) VALUES ($1, $2, 'grant', $3)",
+            user_id,
+            role.as_str(),
+            granted_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Revoca el rol. El recuento de administradores y el borrado van en la misma transacción,
+    /// con las filas de administrador bloqueadas, para que nunca quede la plataforma sin ninguno.
+    pub async fn revoke(&self, user_id: Uuid, role: Role, revoked_by: Uuid) -> Result<RoleRevocation> {
+        let mut tx = self.pool.begin().await?;
+
+        if role == Role::Admin {
+            let admins = sqlx::query_scalar!(
+                "SELECT user_id FROM user_roles WHERE role = 'admin' FOR UPDATE"
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            if admins.contains(&user_id) && admins.len() <= 1 {
+                return Ok(RoleRevocation::LastAdmin);
+            }
+        }
+
+        let result = sqlx::query!(
+            "DELETE FROM user_roles WHERE user_id = $1 AND role = $2",
+            user_id,
+            role.as_str()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(RoleRevocation::NotAssigned);
+        }
+
+        // Invalida los access tokens emitidos con el rol; al renovarlos ya no lo incluyen
+        sqlx::query!(
+            "UPDATE users SET token_version = token_version + 1 WHERE id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO role_changes (user_id, role, action, changed_by) VALUES ($1, $2, 'revoke', $3)",
+            user_id,
+            role.as_str(),
+            revoked_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(RoleRevocation::Revoked)
+    }
+}
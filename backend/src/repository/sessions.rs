@@ -0,0 +1,134 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::Session;
+
+pub struct SessionRepository {
+    pool: PgPool,
+}
+
+impl SessionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session> {
+        let session = sqlx::query_as!(
+            Session,
+            r#"
+            INSERT INTO sessions (user_id, user_agent, ip_address, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+            user_id,
+            user_agent,
+            ip_address,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// Comprueba que la sesión siga viva y actualiza `last_seen_at` (como mucho una vez por minuto).
+    pub async fn touch(&self, session_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let session = sqlx::query!(
+            r#"
+            SELECT (revoked_at IS NULL AND expires_at > NOW()) as "active!",
+                   last_seen_at < NOW() - INTERVAL '1 minute' as "stale!"
+            FROM sessions
+            WHERE id = $1 AND user_id = $2
+            "#,
+            session_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(session) = session else {
+            return Ok(false);
+        };
+
+        if session.active && session.stale {
+            sqlx::query!(
+                "UPDATE sessions SET last_seen_at = NOW() WHERE id = $1",
+                session_id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(session.active)
+    }
+
+    pub async fn list_active(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as!(
+            Session,
+            r#"
+            SELECT * FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY last_seen_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Revoca una sesión y todos sus refresh tokens. Devuelve `false` si no existía o ya estaba revocada.
+    pub async fn revoke(&self, session_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE sessions SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE session_id = $1 AND revoked_at IS NULL",
+            session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
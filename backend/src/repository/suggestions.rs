@@ -0,0 +1,281 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::SuggestedUser;
+
+// Límites para que el cálculo por usuario no crezca con el tamaño de la red
+const MAX_FOLLOWS_CONSIDERED: i64 = 500;
+const MAX_LIKES_CONSIDERED: i64 = 200;
+const POPULAR_CANDIDATES: i64 = 50;
+const MAX_FOLLOWERS_REFRESHED: i64 = 1000;
+
+pub struct SuggestionRepository {
+    pool: PgPool,
+}
+
+impl SuggestionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Pide recalcular las sugerencias del usuario en la próxima pasada del worker.
+    pub async fn mark_stale(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO suggestion_queue (user_id) VALUES ($1)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Encola a los seguidores más recientes del usuario: sus seguidos forman parte de los
+    /// candidatos de ellos. El resto se recalcula cuando sus sugerencias caducan.
+    pub async fn mark_followers_stale(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO suggestion_queue (user_id)
+            SELECT follower_id FROM follows
+            WHERE following_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+            user_id,
+            MAX_FOLLOWERS_REFRESHED
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saca de la cola hasta `limit` usuarios; otras instancias no toman los mismos.
+    pub async fn claim_stale(&self, limit: i64) -> Result<Vec<Uuid>> {
+        let user_ids = sqlx::query_scalar!(
+            r#"
+            DELETE FROM suggestion_queue
+            WHERE user_id IN (
+                SELECT user_id FROM suggestion_queue
+                ORDER BY queued_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING user_id
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(user_ids)
+    }
+
+    /// Recalcula y sustituye las sugerencias del usuario. Candidatos: cuentas seguidas por sus
+    /// seguidos, cuentas que dieron like a los mismos posts y las más seguidas de la red.
+    pub async fn recompute(&self, user_id: Uuid, keep: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM follow_suggestions WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            WITH my_follows AS (
+                SELECT following_id FROM follows
+                WHERE follower_id = $1
+                ORDER BY created_at DESC
+                LIMIT $3
+            ), friends_of_friends AS (
+                SELECT f.following_id AS candidate, COUNT(*) AS mutual_count
+                FROM follows f
+                JOIN my_follows m ON m.following_id = f.follower_id
+                GROUP BY f.following_id
+            ), my_likes AS (
+                SELECT post_id FROM likes
+                WHERE user_id = $1
+                ORDER BY created_at DESC
+                LIMIT $4
+            ), co_likers AS (
+                SELECT l.user_id AS candidate, COUNT(*) AS shared_likes_count
+                FROM likes l
+                JOIN my_likes ml ON ml.post_id = l.post_id
+                GROUP BY l.user_id
+            ), popular AS (
+                SELECT id AS candidate FROM users
+                WHERE is_active = true
+                ORDER BY followers_count DESC NULLS LAST
+                LIMIT $5
+            ), candidates AS (
+                SELECT candidate FROM friends_of_friends
+                UNION SELECT candidate FROM co_likers
+                UNION SELECT candidate FROM popular
+            )
+            INSERT INTO follow_suggestions (user_id, suggested_id, score, mutual_count, shared_likes_count)
+            SELECT
+                $1,
+                u.id,
+                3 * COALESCE(fof.mutual_count, 0)
+                    + COALESCE(cl.shared_likes_count, 0)
+                    + ln(1 + COALESCE(u.followers_count, 0)),
+                COALESCE(fof.mutual_count, 0),
+                COALESCE(cl.shared_likes_count, 0)
+            FROM candidates c
+            JOIN users u ON u.id = c.candidate
+            LEFT JOIN friends_of_friends fof ON fof.candidate = u.id
+            LEFT JOIN co_likers cl ON cl.candidate = u.id
+            WHERE u.id <> $1
+              AND u.is_active = true
+              AND NOT EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM follow_requests WHERE requester_id = $1 AND target_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM suggestion_dismissals WHERE user_id = $1 AND suggested_id = u.id)
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $1)
+              )
+            ORDER BY 3 DESC
+            LIMIT $2
+            "#,
+            user_id,
+            keep,
+            MAX_FOLLOWS_CONSIDERED,
+            MAX_LIKES_CONSIDERED,
+            POPULAR_CANDIDATES
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO suggestion_state (user_id) VALUES ($1)
+            ON CONFLICT (user_id) DO UPDATE SET computed_at = NOW()
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Cuándo se calcularon por última vez las sugerencias del usuario; `None` si nunca.
+    pub async fn computed_at(&self, user_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        let computed_at = sqlx::query_scalar!(
+            "SELECT computed_at FROM suggestion_state WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(computed_at)
+    }
+
+    /// Sugerencias precalculadas, descartando las que dejaron de aplicar desde el cálculo.
+    pub async fn for_user(&self, user_id: Uuid, limit: i64) -> Result<Vec<SuggestedUser>> {
+        let suggestions = sqlx::query_as!(
+            SuggestedUser,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                u.followers_count as "followers_count!",
+                s.mutual_count,
+                s.shared_likes_count
+            FROM follow_suggestions s
+            JOIN users u ON u.id = s.suggested_id
+            WHERE s.user_id = $1
+              AND u.is_active = true
+              AND NOT EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM follow_requests WHERE requester_id = $1 AND target_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM suggestion_dismissals WHERE user_id = $1 AND suggested_id = u.id)
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $1)
+              )
+            ORDER BY s.score DESC, u.id
+            LIMIT $2
+            "#,
+            user_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(suggestions)
+    }
+
+    /// Cuentas más seguidas, mientras las sugerencias del usuario aún no se han calculado.
+    pub async fn popular(&self, user_id: Uuid, limit: i64) -> Result<Vec<SuggestedUser>> {
+        let suggestions = sqlx::query_as!(
+            SuggestedUser,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                u.followers_count as "followers_count!",
+                0 as "mutual_count!",
+                0 as "shared_likes_count!"
+            FROM users u
+            WHERE u.id <> $1
+              AND u.is_active = true
+              AND NOT EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM follow_requests WHERE requester_id = $1 AND target_id = u.id)
+              AND NOT EXISTS(SELECT 1 FROM suggestion_dismissals WHERE user_id = $1 AND suggested_id = u.id)
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $1)
+              )
+            ORDER BY u.followers_count DESC NULLS LAST, u.id
+            LIMIT $2
+            "#,
+            user_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(suggestions)
+    }
+
+    pub async fn dismiss(&self, user_id: Uuid, suggested_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO suggestion_dismissals (user_id, suggested_id) VALUES ($1, $2)
+            ON CONFLICT (user_id, suggested_id) DO NOTHING
+            "#,
+            user_id,
+            suggested_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM follow_suggestions WHERE user_id = $1 AND suggested_id = $2",
+            user_id,
+            suggested_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{TwoFactorChallenge, UserTotp};
+
+pub struct TwoFactorRepository {
+    pool: PgPool,
+}
+
+impl TwoFactorRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, user_id: Uuid) -> Result<Option<UserTotp>> {
+        let totp = sqlx::query_as!(
+            UserTotp,
+            "SELECT * FROM user_totp WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(totp)
+    }
+
+    pub async fn is_enabled(&self, user_id: Uuid) -> Result<bool> {
+        let enabled = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM user_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL) as "enabled!""#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(enabled)
+    }
+
+    /// Guarda un secreto pendiente de confirmar, sustituyendo cualquier alta anterior sin confirmar.
+    /// Devuelve `false` si el usuario ya tiene 2FA activo.
+    pub async fn start_enrollment(&self, user_id: Uuid, secret: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO user_totp (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+            WHERE user_totp.confirmed_at IS NULL
+            "#,
+            user_id,
+            secret
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Activa el 2FA y reemplaza los códigos de recuperación.
+    pub async fn confirm(&self, user_id: Uuid, step: i64, recovery_code_hashes: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE user_totp SET confirmed_at = NOW(), last_used_step = $2 WHERE user_id = $1",
+            user_id,
+            step
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO totp_recovery_codes (user_id, code_hash)
+            SELECT $1, code_hash FROM UNNEST($2::varchar[]) AS code_hash
+            "#,
+            user_id,
+            recovery_code_hashes
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Registra el paso de tiempo usado; falla si ese código (o uno posterior) ya se había usado.
+    pub async fn record_step(&self, user_id: Uuid, step: i64) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_totp SET last_used_step = $2
+            WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+            "#,
+            user_id,
+            step
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn use_recovery_code(&self, user_id: Uuid, code_hash: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE totp_recovery_codes SET used_at = NOW()
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            "#,
+            user_id,
+            code_hash
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn disable(&self, user_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM totp_recovery_codes WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn create_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<TwoFactorChallenge> {
+        let challenge = sqlx::query_as!(
+            TwoFactorChallenge,
+            r#"
+            INSERT INTO two_factor_challenges (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Busca un reto vigente y cuenta el intento; los retos agotados o usados no se devuelven.
+    pub async fn attempt_challenge(&self, token_hash: &str, max_attempts: i32) -> Result<Option<TwoFactorChallenge>> {
+        let challenge = sqlx::query_as!(
+            TwoFactorChallenge,
+            r#"
+            UPDATE two_factor_challenges SET attempts = attempts + 1
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW() AND attempts < $2
+            RETURNING *
+            "#,
+            token_hash,
+            max_attempts
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    pub async fn consume_challenge(&self, challenge_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE two_factor_challenges SET used_at = NOW() WHERE id = $1 AND used_at IS NULL",
+            challenge_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use sqlx::PgPool;
 use uuid::Uuid;
-use crate::models::{User, CreateUser};
+use chrono::{DateTime, Utc};
+use crate::models::{AccountState, User, CreateUser, UserSearchResult};
 use crate::auth::hash_password;
+use super::verifications::{cancel_pending_request, revoke_verification};
 
 pub struct UserRepository {
     pool: PgPool,
@@ -14,14 +16,22 @@ impl UserRepository {
     }
 
     pub async fn create_user(&self, data: &CreateUser) -> Result<User> {
-        let password_hash = hash_password(&data.password)?;
+        let password_hash = hash_password(&data.password).await?;
         
         let user = sqlx::query_as!(
             User,
             r#"
             INSERT INTO users (username, email, password_hash, display_name)
             VALUES ($1, $2, $3, $4)
-            RETURNING *
+            RETURNING id, username, email, password_hash, display_name, bio, avatar_url,
+                      followers_count as "followers_count!", following_count as "following_count!",
+                      posts_count as "posts_count!", is_verified as "is_verified!",
+                      is_active as "is_active!", created_at as "created_at!",
+                      updated_at as "updated_at!", email_verified_at, token_version,
+                      password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                      deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                      birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                      verification_badge, verified_at
             "#,
             data.username,
             data.email,
@@ -37,7 +47,45 @@ impl UserRepository {
     pub async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
         let user = sqlx::query_as!(
             User,
-            "SELECT * FROM users WHERE username = $1 AND is_active = true",
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE username = $1 AND is_active = true
+            "#,
+            username
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Cuenta para el login: activa, o desactivada con la eliminación aún por ejecutar.
+    pub async fn find_for_login(&self, username: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE username = $1
+              AND (is_active = true OR deletion_scheduled_at > NOW())
+            "#,
             username
         )
         .fetch_optional(&self.pool)
@@ -46,10 +94,130 @@ impl UserRepository {
         Ok(user)
     }
 
+    /// Búsqueda aproximada por nombre de usuario y nombre visible, sin tildes ni mayúsculas.
+    /// Ordena por parecido, relación con quien busca y número de seguidores; las cuentas con
+    /// bloqueos de por medio no aparecen.
+    pub async fn search(&self, query: &str, viewer_id: Option<Uuid>, limit: i64, offset: i64) -> Result<Vec<UserSearchResult>> {
+        // El término también se usa en un LIKE: sus comodines se escapan
+        let pattern = format!("%{}%", escape_like(query));
+
+        let results = sqlx::query_as!(
+            UserSearchResult,
+            r#"
+            WITH q AS (SELECT search_normalize($1) AS term, search_normalize($5) AS pattern),
+            matches AS (
+                SELECT
+                    u.*,
+                    word_similarity(q.term, search_normalize(u.username || ' ' || COALESCE(u.display_name, ''))) AS score,
+                    EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = u.id) AS viewer_follows,
+                    EXISTS(SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $2) AS follows_viewer
+                FROM users u, q
+                WHERE u.is_active = true
+                  AND (
+                      search_normalize(u.username || ' ' || COALESCE(u.display_name, '')) %> q.term
+                      OR search_normalize(u.username || ' ' || COALESCE(u.display_name, '')) LIKE q.pattern
+                  )
+                  AND NOT EXISTS(
+                      SELECT 1 FROM user_blocks b
+                      WHERE (b.blocker_id = $2 AND b.blocked_id = u.id)
+                         OR (b.blocker_id = u.id AND b.blocked_id = $2)
+                  )
+            )
+            SELECT
+                m.id as "id!",
+                m.username as "username!",
+                m.display_name,
+                m.avatar_url,
+                m.is_verified as "is_verified!",
+                m.is_private as "is_private!",
+                m.followers_count as "followers_count!",
+                CASE WHEN $2::uuid IS NULL THEN NULL ELSE m.viewer_follows END as "you_follow: bool",
+                CASE WHEN $2::uuid IS NULL THEN NULL ELSE m.follows_viewer END as "follows_you: bool"
+            FROM matches m, q
+            ORDER BY
+                m.score
+                    + CASE WHEN lower(m.username) = q.term THEN 1 ELSE 0 END
+                    + CASE WHEN m.viewer_follows THEN 0.3 ELSE 0 END
+                    + CASE WHEN m.follows_viewer THEN 0.15 ELSE 0 END
+                    + LEAST(ln(1 + COALESCE(m.followers_count, 0)) / 50, 0.2) DESC,
+                m.id
+            LIMIT $3 OFFSET $4
+            "#,
+            query,
+            viewer_id,
+            limit,
+            offset,
+            pattern
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    /// Autocompletado de menciones: nombres de usuario que empiezan por `prefix`, primero las
+    /// cuentas que sigue quien escribe y después las más seguidas.
+    pub async fn autocomplete(&self, prefix: &str, viewer_id: Option<Uuid>, limit: i64) -> Result<Vec<UserSearchResult>> {
+        // El prefijo se usa en un LIKE: sus comodines se escapan
+        let pattern = format!("{}%", escape_like(&prefix.to_lowercase()));
+
+        let results = sqlx::query_as!(
+            UserSearchResult,
+            r#"
+            SELECT
+                u.id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.is_verified as "is_verified!",
+                u.is_private,
+                u.followers_count as "followers_count!",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = u.id)
+                END as "you_follow: bool",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS(SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $2)
+                END as "follows_you: bool"
+            FROM users u
+            WHERE lower(u.username) LIKE $1
+              AND u.is_active = true
+              AND NOT EXISTS(
+                  SELECT 1 FROM user_blocks b
+                  WHERE (b.blocker_id = $2 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $2)
+              )
+            ORDER BY
+                EXISTS(SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = u.id) DESC,
+                u.followers_count DESC NULLS LAST,
+                u.username
+            LIMIT $3
+            "#,
+            pattern,
+            viewer_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
     pub async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
         let user = sqlx::query_as!(
             User,
-            "SELECT * FROM users WHERE email = $1 AND is_active = true",
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1) AND is_active = true
+            "#,
             email
         )
         .fetch_optional(&self.pool)
@@ -58,10 +226,75 @@ impl UserRepository {
         Ok(user)
     }
 
+    /// Como `find_by_email`, sin distinguir mayúsculas e incluyendo las cuentas pendientes de
+    /// eliminación, que se recuperan al iniciar sesión.
+    pub async fn find_by_email_for_login(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1)
+              AND (is_active = true OR deletion_scheduled_at > NOW())
+            "#,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Como `find_by_id`, incluyendo las cuentas pendientes de eliminación.
+    pub async fn find_by_id_for_login(&self, id: Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE id = $1
+              AND (is_active = true OR deletion_scheduled_at > NOW())
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
         let user = sqlx::query_as!(
             User,
-            "SELECT * FROM users WHERE id = $1 AND is_active = true",
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE id = $1 AND is_active = true
+            "#,
             id
         )
         .fetch_optional(&self.pool)
@@ -69,4 +302,459 @@ impl UserRepository {
 
         Ok(user)
     }
+
+    pub async fn update_password_hash(&self, id: Uuid, password_hash: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE users SET password_hash = $2 WHERE id = $1",
+            id,
+            password_hash
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Estado de la cuenta y de la sesión en una sola consulta; `None` si la sesión no existe.
+    pub async fn account_state(&self, user_id: Uuid, session_id: Uuid) -> Result<Option<AccountState>> {
+        let state = sqlx::query_as!(
+            AccountState,
+            r#"
+            SELECT (s.revoked_at IS NULL AND s.expires_at > NOW()) as "session_active!",
+                   u.is_active as "is_active!",
+                   (u.banned_at IS NOT NULL AND (u.banned_until IS NULL OR u.banned_until > NOW())) as "is_banned!",
+                   u.token_version
+            FROM sessions s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.id = $1 AND s.user_id = $2
+            "#,
+            session_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    /// Suspende la cuenta y cierra todas sus sesiones.
+    pub async fn ban(&self, id: Uuid, reason: Option<&str>, until: Option<DateTime<Utc>>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET banned_at = NOW(), banned_until = $2, ban_reason = $3,
+                token_version = token_version + 1, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            until,
+            reason
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn unban(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users SET banned_at = NULL, banned_until = NULL, ban_reason = NULL, updated_at = NOW()
+            WHERE id = $1 AND banned_at IS NOT NULL
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Cambia la contraseña y cierra las demás sesiones y los tokens personales; conserva `keep_session`.
+    pub async fn change_password(&self, id: Uuid, password_hash: &str, keep_session: Option<Uuid>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users SET password_hash = $2, password_changed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            password_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE sessions SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL AND id IS DISTINCT FROM $2
+            "#,
+            id,
+            keep_session
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL AND session_id IS DISTINCT FROM $2
+            "#,
+            id,
+            keep_session
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE personal_access_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Libre si ninguna otra cuenta (activa o no) lo usa ni lo usó durante el periodo de gracia
+    /// (`since`). `user_id` es la cuenta que lo pide, o `None` en un registro nuevo.
+    pub async fn is_username_available(&self, username: &str, user_id: Option<Uuid>, since: DateTime<Utc>) -> Result<bool> {
+        let available = sqlx::query_scalar!(
+            r#"
+            SELECT NOT EXISTS (
+                   SELECT 1 FROM users WHERE LOWER(username) = LOWER($1) AND id IS DISTINCT FROM $2
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM username_history
+                   WHERE LOWER(old_username) = LOWER($1) AND user_id IS DISTINCT FROM $2 AND changed_at > $3
+               ) AS "available!"
+            "#,
+            username,
+            user_id,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(available)
+    }
+
+    /// Guarda los campos editables del perfil tal como vienen en `user`.
+    pub async fn update_profile(&self, user: &User) -> Result<User> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET display_name = $2, bio = $3, website = $4, location = $5, pronouns = $6,
+                birthday = $7, birthday_visibility = $8, is_private = $9, updated_at = NOW()
+            WHERE id = $1 AND is_active = true
+            RETURNING id, username, email, password_hash, display_name, bio, avatar_url,
+                      followers_count as "followers_count!", following_count as "following_count!",
+                      posts_count as "posts_count!", is_verified as "is_verified!",
+                      is_active as "is_active!", created_at as "created_at!",
+                      updated_at as "updated_at!", email_verified_at, token_version,
+                      password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                      deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                      birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                      verification_badge, verified_at
+            "#,
+            user.id,
+            user.display_name,
+            user.bio,
+            user.website,
+            user.location,
+            user.pronouns,
+            user.birthday,
+            user.birthday_visibility,
+            user.is_private
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Cambia (o quita, con `None`) el avatar y devuelve la clave del anterior para borrarlo.
+    pub async fn set_avatar(&self, id: Uuid, url: Option<&str>, key: Option<&str>) -> Result<Option<String>> {
+        let old_key = sqlx::query_scalar!(
+            r#"
+            WITH old AS (SELECT avatar_key FROM users WHERE id = $1 FOR UPDATE)
+            UPDATE users u SET avatar_url = $2, avatar_key = $3, updated_at = NOW()
+            FROM old
+            WHERE u.id = $1
+            RETURNING old.avatar_key
+            "#,
+            id,
+            url,
+            key
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(old_key)
+    }
+
+    /// Igual que `set_avatar`, para el banner del perfil.
+    pub async fn set_banner(&self, id: Uuid, url: Option<&str>, key: Option<&str>) -> Result<Option<String>> {
+        let old_key = sqlx::query_scalar!(
+            r#"
+            WITH old AS (SELECT banner_key FROM users WHERE id = $1 FOR UPDATE)
+            UPDATE users u SET banner_url = $2, banner_key = $3, updated_at = NOW()
+            FROM old
+            WHERE u.id = $1
+            RETURNING old.banner_key
+            "#,
+            id,
+            url,
+            key
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(old_key)
+    }
+
+    /// Renombra la cuenta y guarda el nombre anterior para las redirecciones.
+    pub async fn change_username(&self, id: Uuid, username: &str) -> Result<User> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO username_history (user_id, old_username, new_username)
+            SELECT id, username, $2 FROM users WHERE id = $1
+            "#,
+            id,
+            username
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // La verificación acredita la identidad bajo el nombre anterior; hay que volver a pedirla,
+        // y una solicitud pendiente se revisaría con el nombre antiguo
+        revoke_verification(&mut tx, id, None, "Cambio de nombre de usuario").await?;
+        cancel_pending_request(&mut tx, id).await?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users SET username = $2, username_changed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, display_name, bio, avatar_url,
+                      followers_count as "followers_count!", following_count as "following_count!",
+                      posts_count as "posts_count!", is_verified as "is_verified!",
+                      is_active as "is_active!", created_at as "created_at!",
+                      updated_at as "updated_at!", email_verified_at, token_version,
+                      password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                      deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                      birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                      verification_badge, verified_at
+            "#,
+            id,
+            username
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    /// Nombre actual de quien usó `old_username` después de `since`, para redirigir.
+    pub async fn find_renamed(&self, old_username: &str, since: DateTime<Utc>) -> Result<Option<String>> {
+        let username = sqlx::query_scalar!(
+            r#"
+            SELECT u.username FROM username_history h
+            JOIN users u ON u.id = h.user_id
+            WHERE h.old_username = $1 AND h.changed_at > $2 AND u.is_active = true
+            ORDER BY h.changed_at DESC
+            LIMIT 1
+            "#,
+            old_username,
+            since
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(username)
+    }
+
+    /// Desactiva la cuenta, cierra todas sus sesiones y programa la purga para `scheduled_at`.
+    pub async fn schedule_deletion(&self, id: Uuid, scheduled_at: DateTime<Utc>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET is_active = false, deletion_requested_at = NOW(), deletion_scheduled_at = $2,
+                token_version = token_version + 1, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            scheduled_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE personal_access_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Reactiva una cuenta pendiente de eliminación. Devuelve `false` si ya no estaba pendiente.
+    pub async fn cancel_deletion(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users
+            SET is_active = true, deletion_requested_at = NULL, deletion_scheduled_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND deletion_scheduled_at > NOW()
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn due_for_deletion(&self, limit: i64) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM users
+            WHERE is_active = false AND deletion_scheduled_at <= NOW()
+            ORDER BY deletion_scheduled_at
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Borra la cuenta y todo su contenido, descontando antes sus likes y follows de los
+    /// contadores de otros posts y usuarios. Devuelve la cuenta borrada y las rutas de sus
+    /// exportaciones (los archivos no se borran en cascada), o `None` si ya no estaba pendiente.
+    pub async fn purge(&self, id: Uuid) -> Result<Option<(User, Vec<String>)>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Bloquear la fila para no competir con una cancelación de última hora
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, display_name, bio, avatar_url,
+                   followers_count as "followers_count!", following_count as "following_count!",
+                   posts_count as "posts_count!", is_verified as "is_verified!",
+                   is_active as "is_active!", created_at as "created_at!",
+                   updated_at as "updated_at!", email_verified_at, token_version,
+                   password_changed_at, banned_at, banned_until, ban_reason, username_changed_at,
+                   deletion_requested_at, deletion_scheduled_at, website, location, pronouns,
+                   birthday, birthday_visibility, avatar_key, banner_url, banner_key, is_private,
+                   verification_badge, verified_at
+            FROM users
+            WHERE id = $1 AND is_active = false AND deletion_scheduled_at <= NOW()
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(user) = user else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE posts p SET likes_count = GREATEST(p.likes_count - 1, 0)
+            FROM likes l
+            WHERE l.post_id = p.id AND l.user_id = $1 AND p.user_id <> $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users u SET followers_count = GREATEST(u.followers_count - 1, 0)
+            FROM follows f
+            WHERE f.following_id = u.id AND f.follower_id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE users u SET following_count = GREATEST(u.following_count - 1, 0)
+            FROM follows f
+            WHERE f.follower_id = u.id AND f.following_id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let export_files = sqlx::query_scalar!(
+            r#"SELECT file_path AS "file_path!" FROM data_exports WHERE user_id = $1 AND file_path IS NOT NULL"#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Posts, likes, follows, sesiones, tokens y exportaciones se eliminan en cascada
+        sqlx::query!("DELETE FROM users WHERE id = $1", id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some((user, export_files)))
+    }
+}
+
+// Escapa los comodines de LIKE (`\` es el carácter de escape por defecto)
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
 }
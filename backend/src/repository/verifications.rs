@@ -0,0 +1,262 @@
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::models::{
+    CreateVerificationRequest, VerificationChange, VerificationQueueEntry, VerificationRequest,
+};
+
+pub struct VerificationRepository {
+    pool: PgPool,
+}
+
+impl VerificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Registra la solicitud; `None` si el usuario ya tiene una pendiente.
+    pub async fn create(&self, user_id: Uuid, request: &CreateVerificationRequest) -> Result<Option<VerificationRequest>> {
+        let request = sqlx::query_as!(
+            VerificationRequest,
+            r#"
+            INSERT INTO verification_requests (user_id, badge_type, legal_name, details, links)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) WHERE status = 'pending' DO NOTHING
+            RETURNING *
+            "#,
+            user_id,
+            request.badge_type.as_str(),
+            request.legal_name.trim(),
+            request.details.trim(),
+            &request.links
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    pub async fn latest_for_user(&self, user_id: Uuid) -> Result<Option<VerificationRequest>> {
+        let request = sqlx::query_as!(
+            VerificationRequest,
+            r#"
+            SELECT * FROM verification_requests
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(request)
+    }
+
+    /// Retira la solicitud pendiente del usuario; `false` si no tenía ninguna.
+    pub async fn cancel(&self, user_id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let cancelled = cancel_pending_request(&mut tx, user_id).await?;
+        tx.commit().await?;
+        Ok(cancelled)
+    }
+
+    /// Cola de revisión: las solicitudes en `status`, de la más antigua a la más reciente.
+    pub async fn queue(&self, status: &str, limit: i64, offset: i64) -> Result<Vec<VerificationQueueEntry>> {
+        let entries = sqlx::query_as!(
+            VerificationQueueEntry,
+            r#"
+            SELECT
+                r.id,
+                r.user_id,
+                u.username,
+                u.display_name,
+                u.avatar_url,
+                u.followers_count as "followers_count!",
+                (u.email_verified_at IS NOT NULL) as "email_verified!",
+                r.badge_type,
+                r.legal_name,
+                r.details,
+                r.links,
+                r.status,
+                r.reviewed_by,
+                r.review_reason,
+                r.created_at,
+                r.reviewed_at
+            FROM verification_requests r
+            JOIN users u ON u.id = r.user_id
+            WHERE r.status = $1
+            ORDER BY r.created_at
+            LIMIT $2 OFFSET $3
+            "#,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Aprueba la solicitud pendiente y concede la insignia; `None` si ya no estaba pendiente.
+    pub async fn approve(&self, id: Uuid, reviewer_id: Uuid, reason: Option<&str>) -> Result<Option<VerificationRequest>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(request) = review(&mut tx, id, "approved", reviewer_id, reason).await? else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE users SET is_verified = true, verification_badge = $2, verified_at = NOW()
+            WHERE id = $1
+            "#,
+            request.user_id,
+            request.badge_type
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO verification_changes (user_id, action, badge_type, request_id, reason, changed_by)
+            VALUES ($1, 'grant', $2, $3, $4, $5)
+            "#,
+            request.user_id,
+            request.badge_type,
+            request.id,
+            reason,
+            reviewer_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(request))
+    }
+
+    /// Rechaza la solicitud pendiente; `None` si ya no estaba pendiente.
+    pub async fn reject(&self, id: Uuid, reviewer_id: Uuid, reason: &str) -> Result<Option<VerificationRequest>> {
+        let mut tx = self.pool.begin().await?;
+        let request = review(&mut tx, id, "rejected", reviewer_id, Some(reason)).await?;
+        tx.commit().await?;
+        Ok(request)
+    }
+
+    /// Retira la insignia; `false` si la cuenta no estaba verificada.
+    pub async fn revoke(&self, user_id: Uuid, revoked_by: Uuid, reason: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let revoked = revoke_verification(&mut tx, user_id, Some(revoked_by), reason).await?;
+        tx.commit().await?;
+        Ok(revoked)
+    }
+
+    /// Historial de verificaciones de la cuenta, de la más reciente a la más antigua.
+    pub async fn history(&self, user_id: Uuid) -> Result<Vec<VerificationChange>> {
+        let changes = sqlx::query_as!(
+            VerificationChange,
+            r#"
+            SELECT
+                c.id,
+                c.action,
+                c.badge_type,
+                c.request_id,
+                c.reason,
+                c.changed_by,
+                u.username as "changed_by_username?",
+                c.created_at
+            FROM verification_changes c
+            LEFT JOIN users u ON u.id = c.changed_by
+            WHERE c.user_id = $1
+            ORDER BY c.created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(changes)
+    }
+}
+
+// Cierra la solicitud si sigue pendiente
+async fn review(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    status: &str,
+    reviewer_id: Uuid,
+    reason: Option<&str>,
+) -> Result<Option<VerificationRequest>> {
+    let request = sqlx::query_as!(
+        VerificationRequest,
+        r#"
+        UPDATE verification_requests
+        SET status = $2, reviewed_by = $3, review_reason = $4, reviewed_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING *
+        "#,
+        id,
+        status,
+        reviewer_id,
+        reason
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(request)
+}
+
+// Cancela la solicitud pendiente del usuario, si la hay
+pub(super) async fn cancel_pending_request(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE verification_requests SET status = 'cancelled'
+        WHERE user_id = $1 AND status = 'pending'
+        "#,
+        user_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Quita la insignia y lo deja en el historial; `revoked_by` es None cuando lo hace el sistema
+pub(super) async fn revoke_verification(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    revoked_by: Option<Uuid>,
+    reason: &str,
+) -> Result<bool> {
+    let badge = sqlx::query_scalar!(
+        r#"
+        UPDATE users u SET is_verified = false, verification_badge = NULL, verified_at = NULL
+        FROM (SELECT id, verification_badge FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id AND u.is_verified = true
+        RETURNING old.verification_badge
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(badge) = badge else {
+        return Ok(false);
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO verification_changes (user_id, action, badge_type, reason, changed_by)
+        VALUES ($1, 'revoke', $2, $3, $4)
+        "#,
+        user_id,
+        badge.unwrap_or_else(|| "person".to_string()),
+        reason,
+        revoked_by
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(true)
+}
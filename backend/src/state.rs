@@ -0,0 +1,87 @@
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::auth::{AccountStateCache, JwtService};
+use crate::mailer::Mailer;
+use crate::oauth::OAuthClient;
+use crate::repository::{
+    AccessTokenRepository, BlockRepository, EmailVerificationRepository, ExportRepository,
+    FollowRepository, IdentityRepository, LoginLockoutRepository, MagicLinkRepository,
+    PasswordResetRepository, PostRepository, RefreshTokenRepository, RoleRepository, SessionRepository,
+    SuggestionRepository, TwoFactorRepository, UserRepository, VerificationRepository,
+};
+use crate::storage::{self, Storage};
+use crate::throttle::LoginGuard;
+
+// Estado compartido de la aplicación; cada handler extrae sólo lo que necesita
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub user_repo: Arc<UserRepository>,
+    pub post_repo: Arc<PostRepository>,
+    pub refresh_token_repo: Arc<RefreshTokenRepository>,
+    pub session_repo: Arc<SessionRepository>,
+    pub password_reset_repo: Arc<PasswordResetRepository>,
+    pub email_verification_repo: Arc<EmailVerificationRepository>,
+    pub two_factor_repo: Arc<TwoFactorRepository>,
+    pub login_lockout_repo: Arc<LoginLockoutRepository>,
+    pub role_repo: Arc<RoleRepository>,
+    pub access_token_repo: Arc<AccessTokenRepository>,
+    pub identity_repo: Arc<IdentityRepository>,
+    pub magic_link_repo: Arc<MagicLinkRepository>,
+    pub export_repo: Arc<ExportRepository>,
+    pub follow_repo: Arc<FollowRepository>,
+    pub block_repo: Arc<BlockRepository>,
+    pub suggestion_repo: Arc<SuggestionRepository>,
+    pub verification_repo: Arc<VerificationRepository>,
+    pub mailer: Arc<dyn Mailer>,
+    pub jwt_service: Arc<JwtService>,
+    pub account_cache: Arc<AccountStateCache>,
+    pub login_guard: Arc<LoginGuard>,
+    pub oauth_client: Arc<OAuthClient>,
+    pub storage: Arc<dyn Storage>,
+}
+
+impl AppState {
+    pub async fn new(pool: PgPool, mailer: Arc<dyn Mailer>) -> anyhow::Result<Self> {
+        let jwt_service = Arc::new(JwtService::from_env()?);
+        let user_repo = Arc::new(UserRepository::new(pool.clone()));
+        let session_repo = Arc::new(SessionRepository::new(pool.clone()));
+        let access_token_repo = Arc::new(AccessTokenRepository::new(pool.clone()));
+        let account_cache = Arc::new(AccountStateCache::from_env(
+            user_repo.clone(),
+            session_repo.clone(),
+            access_token_repo.clone(),
+        ));
+        let login_lockout_repo = Arc::new(LoginLockoutRepository::new(pool.clone()));
+        let login_guard = Arc::new(LoginGuard::from_env(pool.clone(), login_lockout_repo.clone()).await?);
+        let oauth_client = Arc::new(OAuthClient::from_env().await?);
+        let storage = storage::from_env()?;
+
+        Ok(Self {
+            user_repo,
+            post_repo: Arc::new(PostRepository::new(pool.clone())),
+            refresh_token_repo: Arc::new(RefreshTokenRepository::new(pool.clone())),
+            session_repo,
+            password_reset_repo: Arc::new(PasswordResetRepository::new(pool.clone())),
+            email_verification_repo: Arc::new(EmailVerificationRepository::new(pool.clone())),
+            two_factor_repo: Arc::new(TwoFactorRepository::new(pool.clone())),
+            role_repo: Arc::new(RoleRepository::new(pool.clone())),
+            identity_repo: Arc::new(IdentityRepository::new(pool.clone())),
+            magic_link_repo: Arc::new(MagicLinkRepository::new(pool.clone())),
+            export_repo: Arc::new(ExportRepository::new(pool.clone())),
+            follow_repo: Arc::new(FollowRepository::new(pool.clone())),
+            block_repo: Arc::new(BlockRepository::new(pool.clone())),
+            suggestion_repo: Arc::new(SuggestionRepository::new(pool.clone())),
+            verification_repo: Arc::new(VerificationRepository::new(pool)),
+            login_lockout_repo,
+            access_token_repo,
+            mailer,
+            jwt_service,
+            account_cache,
+            login_guard,
+            oauth_client,
+            storage,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use axum::async_trait;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::handlers::api_url;
+
+/// Almacenamiento de archivos subidos por los usuarios, direccionados por clave
+/// (`avatars/<usuario>/<id>/400.webp`).
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    /// Contenido de la clave, o `None` si no existe.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    /// URL pública desde la que se sirve la clave.
+    fn url(&self, key: &str) -> String;
+    /// Clave de una URL servida por este almacenamiento; `None` si es externa.
+    fn key_for_url(&self, url: &str) -> Option<String>;
+}
+
+/// Construye el almacenamiento según `STORAGE` (por ahora sólo `local`, el valor por defecto).
+pub fn from_env() -> Result<Arc<dyn Storage>> {
+    let kind = std::env::var("STORAGE").unwrap_or_else(|_| "local".to_string());
+
+    match kind.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::from_env())),
+        other => Err(anyhow::anyhow!("STORAGE desconocido: {}", other)),
+    }
+}
+
+/// Directorio que la API debe servir en `/media`, si el almacenamiento es local.
+pub fn local_media_dir() -> Option<PathBuf> {
+    match std::env::var("STORAGE").as_deref() {
+        Ok("local") | Err(_) => Some(LocalStorage::dir_from_env()),
+        Ok(_) => None,
+    }
+}
+
+// Archivos en disco, servidos por la propia API
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn from_env() -> Self {
+        let public_url = std::env::var("STORAGE_PUBLIC_URL")
+            .unwrap_or_else(|_| format!("{}/media", api_url()));
+
+        Self {
+            root: Self::dir_from_env(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn dir_from_env() -> PathBuf {
+        PathBuf::from(std::env::var("STORAGE_DIR").unwrap_or_else(|_| "./uploads".to_string()))
+    }
+
+    // Las claves las genera la API, pero nunca deben salirse del directorio raíz
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(anyhow::anyhow!("Clave de almacenamiento inválida: {}", key));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        tokio::fs::write(&path, data).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+
+    fn key_for_url(&self, url: &str) -> Option<String> {
+        url.strip_prefix(&self.public_url)?
+            .strip_prefix('/')
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+    }
+}
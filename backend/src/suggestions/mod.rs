@@ -0,0 +1,68 @@
+use anyhow::Result;
+use chrono::Duration;
+use std::sync::Arc;
+
+use crate::repository::SuggestionRepository;
+
+/// Recalcula en segundo plano las sugerencias de a quién seguir de los usuarios encolados.
+///
+/// Configuración por entorno:
+/// - `SUGGESTIONS_POLL_SECONDS`: cada cuánto se revisa la cola (10 por defecto)
+/// - `SUGGESTIONS_BATCH_SIZE`: usuarios recalculados por pasada (100 por defecto)
+/// - `SUGGESTIONS_PER_USER`: sugerencias guardadas por usuario (50 por defecto)
+pub struct SuggestionWorker {
+    suggestion_repo: Arc<SuggestionRepository>,
+    batch_size: i64,
+    per_user: i64,
+}
+
+impl SuggestionWorker {
+    pub fn from_env(suggestion_repo: Arc<SuggestionRepository>) -> Self {
+        Self {
+            suggestion_repo,
+            batch_size: env_i64("SUGGESTIONS_BATCH_SIZE", 100).max(1),
+            per_user: env_i64("SUGGESTIONS_PER_USER", 50).max(1),
+        }
+    }
+
+    pub fn spawn(self) {
+        let seconds = env_i64("SUGGESTIONS_POLL_SECONDS", 10).max(1) as u64;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(seconds));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.run_once().await {
+                    tracing::error!("Error recalculando sugerencias: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn run_once(&self) -> Result<()> {
+        for user_id in self.suggestion_repo.claim_stale(self.batch_size).await? {
+            if let Err(e) = self.suggestion_repo.recompute(user_id, self.per_user).await {
+                tracing::error!("Sugerencias de {} fallidas: {}", user_id, e);
+                // Vuelve a la cola para el siguiente intento
+                self.suggestion_repo.mark_stale(user_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Antigüedad a partir de la cual las sugerencias se recalculan al consultarlas
+/// (`SUGGESTIONS_MAX_AGE_HOURS`, 24 por defecto).
+pub fn max_age() -> Duration {
+    Duration::hours(env_i64("SUGGESTIONS_MAX_AGE_HOURS", 24).max(1))
+}
+
+fn env_i64(name: &str, default: i64) -> i64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
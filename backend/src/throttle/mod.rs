@@ -0,0 +1,187 @@
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::repository::LoginLockoutRepository;
+
+pub mod postgres;
+pub mod redis;
+
+pub use self::postgres::PostgresAttemptStore;
+pub use self::redis::RedisAttemptStore;
+
+#[derive(Debug, Clone, Default)]
+pub struct AttemptState {
+    pub failures: u32,
+    pub blocked_until: Option<DateTime<Utc>>,
+}
+
+/// Almacén de contadores de intentos fallidos. Postgres funciona siempre; Redis es opcional.
+#[async_trait]
+pub trait AttemptStore: Send + Sync {
+    async fn state(&self, key: &str) -> Result<AttemptState>;
+    /// Suma un fallo dentro de la ventana (reiniciándola si caducó) y devuelve el total.
+    async fn record_failure(&self, key: &str, window: Duration) -> Result<u32>;
+    async fn block(&self, key: &str, until: DateTime<Utc>) -> Result<()>;
+    async fn clear(&self, key: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginGuardConfig {
+    pub max_account_failures: u32,
+    pub max_ip_failures: u32,
+    pub backoff_after: u32,
+    pub max_backoff: Duration,
+    pub lockout: Duration,
+    pub window: Duration,
+}
+
+impl LoginGuardConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_account_failures: env_u32("LOGIN_MAX_ACCOUNT_FAILURES", 10),
+            max_ip_failures: env_u32("LOGIN_MAX_IP_FAILURES", 50),
+            backoff_after: env_u32("LOGIN_BACKOFF_AFTER", 3),
+            max_backoff: Duration::seconds(env_u32("LOGIN_MAX_BACKOFF_SECONDS", 60) as i64),
+            lockout: Duration::minutes(env_u32("LOGIN_LOCKOUT_MINUTES", 15) as i64),
+            window: Duration::minutes(env_u32("LOGIN_ATTEMPT_WINDOW_MINUTES", 15) as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LoginBlock {
+    /// La cuenta superó el umbral y está bloqueada temporalmente (423).
+    AccountLocked { until: DateTime<Utc> },
+    /// La IP superó el umbral (429).
+    IpBlocked { until: DateTime<Utc> },
+    /// Espera exponencial entre intentos fallidos de una cuenta (429).
+    Backoff { until: DateTime<Utc> },
+}
+
+/// Protección contra fuerza bruta en el login: backoff exponencial por cuenta,
+/// bloqueo temporal por cuenta y por IP, y registro de cada bloqueo.
+pub struct LoginGuard {
+    store: Arc<dyn AttemptStore>,
+    lockouts: Arc<LoginLockoutRepository>,
+    config: LoginGuardConfig,
+}
+
+impl LoginGuard {
+    pub fn new(
+        store: Arc<dyn AttemptStore>,
+        lockouts: Arc<LoginLockoutRepository>,
+        config: LoginGuardConfig,
+    ) -> Self {
+        Self { store, lockouts, config }
+    }
+
+    /// Usa Redis si `LOGIN_THROTTLE_BACKEND=redis` (con `REDIS_URL`), si no Postgres.
+    pub async fn from_env(pool: PgPool, lockouts: Arc<LoginLockoutRepository>) -> Result<Self> {
+        let backend = std::env::var("LOGIN_THROTTLE_BACKEND").unwrap_or_else(|_| "postgres".to_string());
+
+        let store: Arc<dyn AttemptStore> = match backend.as_str() {
+            "postgres" => Arc::new(PostgresAttemptStore::new(pool)),
+            "redis" => {
+                let url = std::env::var("REDIS_URL")
+                    .map_err(|_| anyhow::anyhow!("REDIS_URL debe estar definida"))?;
+                Arc::new(RedisAttemptStore::connect(&url).await?)
+            }
+            other => return Err(anyhow::anyhow!("LOGIN_THROTTLE_BACKEND desconocido: {}", other)),
+        };
+
+        Ok(Self::new(store, lockouts, LoginGuardConfig::from_env()))
+    }
+
+    /// Comprueba si se puede intentar el login antes de verificar la contraseña.
+    pub async fn check(&self, username: &str, ip_address: Option<&str>) -> Result<Option<LoginBlock>> {
+        let now = Utc::now();
+
+        if let Some(ip) = ip_address {
+            let state = self.store.state(&ip_key(ip)).await?;
+            if let Some(until) = state.blocked_until.filter(|until| *until > now) {
+                return Ok(Some(LoginBlock::IpBlocked { until }));
+            }
+        }
+
+        let state = self.store.state(&account_key(username)).await?;
+        Ok(state.blocked_until.filter(|until| *until > now).map(|until| {
+            if state.failures >= self.config.max_account_failures {
+                LoginBlock::AccountLocked { until }
+            } else {
+                LoginBlock::Backoff { until }
+            }
+        }))
+    }
+
+    /// Registra un intento fallido y devuelve el bloqueo resultante, si lo hay.
+    pub async fn record_failure(
+        &self,
+        username: &str,
+        ip_address: Option<&str>,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<LoginBlock>> {
+        let now = Utc::now();
+        let mut block = None;
+
+        if let Some(ip) = ip_address {
+            let key = ip_key(ip);
+            let failures = self.store.record_failure(&key, self.config.window).await?;
+
+            if failures >= self.config.max_ip_failures {
+                let until = now + self.config.lockout;
+                self.store.block(&key, until).await?;
+                self.lockouts.record("ip", None, None, Some(ip), failures as i32, until).await?;
+                tracing::warn!("IP {} bloqueada tras {} intentos fallidos de login", ip, failures);
+                block = Some(LoginBlock::IpBlocked { until });
+            }
+        }
+
+        let key = account_key(username);
+        let failures = self.store.record_failure(&key, self.config.window).await?;
+
+        if failures >= self.config.max_account_failures {
+            let until = now + self.config.lockout;
+            self.store.block(&key, until).await?;
+            self.lockouts
+                .record("account", user_id, Some(username), ip_address, failures as i32, until)
+                .await?;
+            tracing::warn!("Cuenta {} bloqueada tras {} intentos fallidos de login", username, failures);
+            return Ok(Some(LoginBlock::AccountLocked { until }));
+        }
+
+        if failures >= self.config.backoff_after {
+            // 1s, 2s, 4s, ... hasta `max_backoff`
+            let exponent = (failures - self.config.backoff_after).min(16);
+            let delay = Duration::seconds(1i64 << exponent).min(self.config.max_backoff);
+            let until = now + delay;
+            self.store.block(&key, until).await?;
+            return Ok(block.or(Some(LoginBlock::Backoff { until })));
+        }
+
+        Ok(block)
+    }
+
+    /// Un login correcto reinicia el contador de la cuenta (no el de la IP).
+    pub async fn record_success(&self, username: &str) -> Result<()> {
+        self.store.clear(&account_key(username)).await
+    }
+}
+
+fn account_key(username: &str) -> String {
+    format!("account:{}", username.to_lowercase())
+}
+
+fn ip_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn env_u32(name: &str, default: u32) -> u32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
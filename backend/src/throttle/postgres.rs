@@ -0,0 +1,80 @@
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use super::{AttemptState, AttemptStore};
+
+pub struct PostgresAttemptStore {
+    pool: PgPool,
+}
+
+impl PostgresAttemptStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AttemptStore for PostgresAttemptStore {
+    async fn state(&self, key: &str) -> Result<AttemptState> {
+        let row = sqlx::query!(
+            "SELECT failures, blocked_until FROM login_attempts WHERE key = $1",
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row
+            .map(|row| AttemptState {
+                failures: row.failures.max(0) as u32,
+                blocked_until: row.blocked_until,
+            })
+            .unwrap_or_default())
+    }
+
+    async fn record_failure(&self, key: &str, window: Duration) -> Result<u32> {
+        let failures = sqlx::query_scalar!(
+            r#"
+            INSERT INTO login_attempts (key, failures)
+            VALUES ($1, 1)
+            ON CONFLICT (key) DO UPDATE SET
+                failures = CASE
+                    WHEN login_attempts.window_started_at < NOW() - make_interval(secs => $2) THEN 1
+                    ELSE login_attempts.failures + 1
+                END,
+                window_started_at = CASE
+                    WHEN login_attempts.window_started_at < NOW() - make_interval(secs => $2) THEN NOW()
+                    ELSE login_attempts.window_started_at
+                END
+            RETURNING failures
+            "#,
+            key,
+            window.num_seconds() as f64
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(failures.max(0) as u32)
+    }
+
+    async fn block(&self, key: &str, until: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            "UPDATE login_attempts SET blocked_until = $2 WHERE key = $1",
+            key,
+            until
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<()> {
+        sqlx::query!("DELETE FROM login_attempts WHERE key = $1", key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use redis::{aio::ConnectionManager, AsyncCommands};
+
+use super::{AttemptState, AttemptStore};
+
+pub struct RedisAttemptStore {
+    connection: ConnectionManager,
+}
+
+impl RedisAttemptStore {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+        Ok(Self { connection })
+    }
+}
+
+fn failures_key(key: &str) -> String {
+    format!("login_attempts:{}:failures", key)
+}
+
+fn blocked_key(key: &str) -> String {
+    format!("login_attempts:{}:blocked_until", key)
+}
+
+#[async_trait]
+impl AttemptStore for RedisAttemptStore {
+    async fn state(&self, key: &str) -> Result<AttemptState> {
+        let mut connection = self.connection.clone();
+        let (failures, blocked_until): (Option<u32>, Option<i64>) = redis::pipe()
+            .get(failures_key(key))
+            .get(blocked_key(key))
+            .query_async(&mut connection)
+            .await?;
+
+        Ok(AttemptState {
+            failures: failures.unwrap_or(0),
+            blocked_until: blocked_until.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        })
+    }
+
+    async fn record_failure(&self, key: &str, window: Duration) -> Result<u32> {
+        let mut connection = self.connection.clone();
+        let failures: u32 = connection.incr(failures_key(key), 1).await?;
+
+        // La ventana empieza con el primer fallo
+        if failures == 1 {
+            let _: () = connection.expire(failures_key(key), window.num_seconds().max(1)).await?;
+        }
+
+        Ok(failures)
+    }
+
+    async fn block(&self, key: &str, until: DateTime<Utc>) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let ttl = (until - Utc::now()).num_seconds().max(1) as u64;
+        let _: () = connection.set_ex(blocked_key(key), until.timestamp(), ttl).await?;
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let _: () = connection.del(&[failures_key(key), blocked_key(key)]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::media::{self, ProfileImage};
+use crate::repository::UserRepository;
+use crate::storage::Storage;
+
+// Cuentas purgadas por pasada; el resto espera a la siguiente
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// Días entre la solicitud de eliminación y la purga (`ACCOUNT_DELETION_GRACE_DAYS`, 30 por defecto).
+pub fn deletion_grace_days() -> i64 {
+    std::env::var("ACCOUNT_DELETION_GRACE_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(30)
+}
+
+/// Tarea en segundo plano que purga las cuentas cuyo periodo de gracia terminó.
+/// Se ejecuta cada `ACCOUNT_PURGE_INTERVAL_SECONDS` (una hora por defecto).
+pub fn spawn_deletion_purge(user_repo: Arc<UserRepository>, storage: Arc<dyn Storage>) {
+    let seconds = std::env::var("ACCOUNT_PURGE_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(3600u64)
+        .max(1);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(seconds));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = purge_due_accounts(&user_repo, storage.as_ref()).await {
+                tracing::error!("Error purgando cuentas eliminadas: {}", e);
+            }
+        }
+    });
+}
+
+async fn purge_due_accounts(user_repo: &UserRepository, storage: &dyn Storage) -> anyhow::Result<()> {
+    for user_id in user_repo.due_for_deletion(PURGE_BATCH_SIZE).await? {
+        if let Some((user, export_files)) = user_repo.purge(user_id).await? {
+            // Los archivos no se borran en cascada con la fila
+            if let Some(key) = user.avatar_key {
+                media::remove(storage, ProfileImage::Avatar, &key).await;
+            }
+            if let Some(key) = user.banner_key {
+                media::remove(storage, ProfileImage::Banner, &key).await;
+            }
+            for path in export_files {
+                if let Err(e) = tokio::fs::remove_file(&path).await {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        tracing::warn!("No se pudo borrar la exportación {}: {}", path, e);
+                    }
+                }
+            }
+
+            tracing::info!("Cuenta {} eliminada definitivamente", user_id);
+        }
+    }
+
+    Ok(())
+}
@@ -4,11 +4,21 @@ export interface User {
   display_name: string | null;
   bio: string | null;
   avatar_url: string | null;
+  banner_url: string | null;
   followers_count: number;
   following_count: number;
   posts_count: number;
   is_verified: boolean;
+  verification_badge: 'person' | 'organization' | 'government' | null;
+  is_private: boolean;
+  website: string | null;
+  location: string | null;
+  pronouns: string | null;
+  birthday: string | null;
   created_at: string;
+  you_follow: boolean | null;
+  follows_you: boolean | null;
+  follow_requested: boolean | null;
 }
 
 export interface AuthResponse {